@@ -0,0 +1,141 @@
+//! The computer player. This is a simple player rather than a strong
+//! engine: it takes the biggest capture it can, otherwise plays away from
+//! the edge of the board, and never fills in its own eyes.
+use crate::game_logic::{
+    board::{Board, Point},
+    position::Position,
+    stone::Stone,
+};
+use std::{cmp::Reverse, collections::HashSet};
+
+/// Pick a move for `stone` in `position`, or `None` if there is nothing
+/// worth playing and it should pass.
+pub(crate) fn generate_move(position: &Position, stone: Stone) -> Option<Point> {
+    if position.to_move() != stone {
+        // a GTP controller may ask for a move of either color
+        let mut turned = position.clone();
+        turned.set_to_move(stone);
+        return generate_move(&turned, stone);
+    }
+    let board = position.board();
+    let mut candidates: Vec<(Point, usize)> = (0..board.width * board.height)
+        .filter_map(|index| board.point_at(index))
+        .filter_map(|point| Some((point, move_value(board, point.into(), stone)?)))
+        .collect();
+    // the sort is stable, so equal moves are tried from the top left
+    candidates.sort_by_key(|&(_, value)| Reverse(value));
+    // the rules may still refuse the best moves, e.g. for ko or suicide
+    candidates
+        .into_iter()
+        .map(|(point, _)| point)
+        .find(|&point| position.is_legal(stone, point))
+}
+
+/// How good a move at `pos` looks for `stone`, `None` if it should not be
+/// played at all.
+fn move_value(board: &Board, pos: (usize, usize), stone: Stone) -> Option<usize> {
+    if board.state[pos.0][pos.1] != Stone::Empty {
+        return None;
+    }
+    let neighbors = board.neighbors(pos);
+    if neighbors
+        .iter()
+        .all(|&(row, col)| board.state[row][col] == stone)
+    {
+        // filling an eye of our own
+        return None;
+    }
+
+    let opponent = stone.get_opponent()?;
+    let mut captured = HashSet::new();
+    for &(row, col) in &neighbors {
+        if board.state[row][col] != opponent || captured.contains(&(row, col)) {
+            continue;
+        }
+        let (chain, liberties) = chain_at(board, (row, col));
+        if liberties.len() == 1 {
+            captured.extend(chain);
+        }
+    }
+    let (row, col) = pos;
+    let from_edge = row
+        .min(col)
+        .min(board.height - 1 - row)
+        .min(board.width - 1 - col);
+    Some(captured.len() * 10 + from_edge.min(3))
+}
+
+type Points = HashSet<(usize, usize)>;
+
+/// The stones of the chain at `pos`, and its liberties.
+fn chain_at(board: &Board, pos: (usize, usize)) -> (Points, Points) {
+    let stone = board.state[pos.0][pos.1];
+    let mut chain = HashSet::from([pos]);
+    let mut liberties = HashSet::new();
+    let mut to_visit = vec![pos];
+    while let Some(pos) = to_visit.pop() {
+        for (row, col) in board.neighbors(pos) {
+            match board.state[row][col] {
+                Stone::Empty => {
+                    liberties.insert((row, col));
+                }
+                adjacent if adjacent == stone && chain.insert((row, col)) => {
+                    to_visit.push((row, col));
+                }
+                _ => {}
+            }
+        }
+    }
+    (chain, liberties)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Play `stone` at `(row, col)`, whoever's turn it is.
+    fn play(position: &mut Position, stone: Stone, (row, col): (usize, usize)) {
+        let point = position.board().point(row, col).unwrap();
+        position.set_to_move(stone);
+        position.play(stone, point).unwrap();
+    }
+
+    /// The move `generate_move` picks, as `(row, col)`.
+    fn generated(position: &Position, stone: Stone) -> Option<(usize, usize)> {
+        generate_move(position, stone).map(<(usize, usize)>::from)
+    }
+
+    #[test]
+    fn takes_captures() {
+        let mut position = Position::new(5, 5);
+        play(&mut position, Stone::Black, (0, 1));
+        play(&mut position, Stone::White, (0, 0));
+        assert_eq!(generated(&position, Stone::Black), Some((1, 0)));
+    }
+
+    #[test]
+    fn plays_away_from_the_edge() {
+        let position = Position::new(5, 5);
+        assert_eq!(generated(&position, Stone::Black), Some((2, 2)));
+    }
+
+    #[test]
+    fn does_not_fill_own_eyes() {
+        let mut position = Position::new(2, 2);
+        for pos in [(0, 1), (1, 0)] {
+            play(&mut position, Stone::Black, pos);
+        }
+        // the two empty corners are both eyes, so there is nothing to play
+        assert_eq!(generated(&position, Stone::Black), None);
+    }
+
+    #[test]
+    fn skips_moves_the_rules_refuse() {
+        let mut position = Position::new(3, 3);
+        for pos in [(0, 1), (1, 0), (1, 2), (2, 1)] {
+            play(&mut position, Stone::Black, pos);
+        }
+        // every empty point would be suicide for White, so it passes
+        assert_eq!(generated(&position, Stone::White), None);
+    }
+}
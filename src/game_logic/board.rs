@@ -13,16 +13,47 @@
 ///
 /// Please note that this file is intended only for representing the game board's state and should not include any gameplay logic.
 /// Game-related operations and rules should be implemented elsewhere in the codebase.
+use crate::game_logic::error::MoveError;
 use crate::game_logic::game_move::*;
+use crate::game_logic::notation::Notation;
+use crate::game_logic::overlay::{point_icon, star_points, Overlay};
 use crate::game_logic::stone::*;
 use colored::{ColoredString, Colorize};
 use std::fmt::Display;
 
+/// A point of a board, as a row counted from the top and a column counted
+/// from the left, both from 0.
+///
+/// Points are made through `Board::point`, which checks them against that
+/// board. A point can still be off a smaller board, so boards look up the
+/// stone on a point through `Board::stone_on`, which checks it again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    row: usize,
+    col: usize,
+}
+
+impl Point {
+    pub fn row(self) -> usize {
+        self.row
+    }
+
+    pub fn col(self) -> usize {
+        self.col
+    }
+}
+
+impl From<Point> for (usize, usize) {
+    fn from(point: Point) -> Self {
+        (point.row, point.col)
+    }
+}
+
 /// This is a struct that represents strictly the `board` state for the game.
 ///
 /// This struct contains a 2d vector to represent the board, and helpful
 /// fields like width and height.
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Board {
     pub(crate) state: Vec<Vec<Stone>>,
     pub(crate) width: usize,
@@ -45,19 +76,83 @@ impl Board {
         self.state.iter().map(|v| v.as_slice()).collect()
     }
 
-    /// This gets the 1d index given the cartesian coordinates
-    pub fn index_of_pos(&self, pos: (usize, usize)) -> usize {
-        // TODO: sanity check on bounds
+    /// The point at `row` and `col` of this board, counted from 0 at the top
+    /// left, or `MoveError::OutOfBounds` if the board does not reach that far.
+    ///
+    /// ```rust
+    /// use go::game::Game;
+    /// use go::{MoveError, RawModeUi};
+    /// let game: Game<RawModeUi> = Game::new_game(9, 9, Default::default());
+    /// let point = game.board().point(2, 6).unwrap();
+    /// assert_eq!((point.row(), point.col()), (2, 6));
+    /// assert_eq!(game.board().point(9, 0), Err(MoveError::OutOfBounds));
+    /// ```
+    pub fn point(&self, row: usize, col: usize) -> Result<Point, MoveError> {
+        if row < self.height && col < self.width {
+            Ok(Point { row, col })
+        } else {
+            Err(MoveError::OutOfBounds)
+        }
+    }
+
+    /// The stone on `point`, or `MoveError::OutOfBounds` if the point is
+    /// off this board.
+    pub(crate) fn stone_on(&self, point: Point) -> Result<Stone, MoveError> {
+        self.state
+            .get(point.row)
+            .and_then(|row| row.get(point.col))
+            .copied()
+            .ok_or(MoveError::OutOfBounds)
+    }
+
+    /// The index of `point` in the flat, row by row, numbering of the
+    /// board's points that chains are kept in.
+    pub fn index_of(&self, point: Point) -> usize {
+        point.row * self.width + point.col
+    }
 
+    /// The point numbered `index` in the flat numbering, see `index_of`.
+    pub fn point_at(&self, index: usize) -> Option<Point> {
+        (self.width > 0)
+            .then(|| self.point(index / self.width, index % self.width).ok())
+            .flatten()
+    }
+
+    /// This gets the 1d index given the cartesian coordinates, or `None`
+    /// when they are off the board.
+    pub fn index_of_pos(&self, pos: (usize, usize)) -> Option<usize> {
+        (pos.0 < self.height && pos.1 < self.width).then(|| self.flat_index(pos))
+    }
+
+    /// The 1d index of coordinates that have to be on the board, like the
+    /// ones `neighbors` gives.
+    pub(crate) fn flat_index(&self, pos: (usize, usize)) -> usize {
+        debug_assert!(pos.0 < self.height && pos.1 < self.width);
         pos.0 * self.width + pos.1
     }
 
-    /// Simply place the stone onto the board
+    /// The in-bounds positions directly above, below, left and right of `pos`.
+    pub(crate) fn neighbors(&self, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        let (row, col) = pos;
+        let manhattan_adjacencies = [
+            (row.checked_sub(1), Some(col)),
+            (row.checked_add(1), Some(col)),
+            (Some(row), col.checked_sub(1)),
+            (Some(row), col.checked_add(1)),
+        ];
+        manhattan_adjacencies
+            .into_iter()
+            .filter_map(|adj| match adj {
+                (Some(r), Some(c)) if r < self.height && c < self.width => Some((r, c)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Simply place the stone onto the board. Its point has to be on the
+    /// board, which `Position` checks through `stone_on` before placing it.
     pub(crate) fn place_stone(&mut self, mv: &GameMove) {
-        // TODO: Sanity check for bounds just in case.
-        let row = mv.pos.0;
-        let col = mv.pos.1;
-        self.state[row][col] = mv.stone;
+        self.state[mv.pos.row][mv.pos.col] = mv.stone;
     }
 
     #[cfg(test)]
@@ -65,89 +160,134 @@ impl Board {
         self.state[row][col]
     }
 
+    /// The icon of `point`, with its mark from `overlay`. `stars` are the
+    /// board's star points, from `star_points`.
+    pub(crate) fn icon_at(
+        &self,
+        point: Point,
+        overlay: &Overlay,
+        stars: &[(usize, usize)],
+    ) -> ColoredString {
+        point_icon(
+            self.state[point.row][point.col],
+            overlay.get(point),
+            stars.contains(&point.into()),
+        )
+    }
+
     /// This function is used to pretty print the board to the terminal,
-    /// which is used for the RawModeUI when playing with the TUI.
-    /// This function is awful, primarily because of the fact that
-    /// we have a Vec of strings. Not a vec of chars. Make sizing
-    /// nightmarish.
-    pub(crate) fn to_ascii(&self) -> Vec<colored::ColoredString> {
-        let mut ascii: Vec<colored::ColoredString> = Vec::new();
-        // legend_max_char_width examples:
-        // '5' is 1 char long
-        // '11' is 2 char long
-        // Each of these need different padding size
-        let legend_max_char_width = self.width.to_string().len();
-        let padding = (0..legend_max_char_width).map(|_| " ").collect::<String>();
-        for (idx, row) in self.state.iter().enumerate() {
-            for i in 0..padding.len() {
-                if i % padding.len() == 0 {
-                    let legend = ((idx + 'A' as usize) as u8) as char;
-                    let legend = legend.to_string() + "┃" + &padding;
-                    ascii.push(legend.white());
-                    // push stones on row
-                    for stone in row {
-                        ascii.push(stone.get_icon());
-                        // add whitespace for better looking board
-                        ascii.push(padding.white());
-                    }
-                    ascii.push("\n".white());
-                } else {
-                    ascii.push(" ┃".white());
-                    ascii.push("\n".white());
-                }
+    /// which is used for the RawModeUI when playing with the TUI. The
+    /// legends name the rows and columns in `notation`, and every point is
+    /// as wide as the widest column legend so the legends line up with it.
+    /// The marks of `overlay` are drawn over the stones, and the star points
+    /// of standard sizes as `+`.
+    pub(crate) fn to_ascii(&self, notation: Notation, overlay: &Overlay) -> Vec<ColoredString> {
+        let mut ascii: Vec<ColoredString> = Vec::new();
+        let row_labels: Vec<String> = (0..self.height)
+            .map(|row| notation.row_label(row, self.height))
+            .collect();
+        let col_labels: Vec<String> = (0..self.width).map(|col| notation.col_label(col)).collect();
+        let layout = Layout::new(self, notation);
+        let row_label_width = layout.row_label_width;
+        let legend_max_char_width = layout.legend_width;
+        let padding = " ".repeat(legend_max_char_width);
+        let margin = " ".repeat(row_label_width);
+        let stars = star_points(self.width, self.height);
+        for (row, label) in row_labels.iter().enumerate() {
+            ascii.push(format!("{label:>row_label_width$}┃{padding}").white());
+            // push stones on row
+            for col in 0..self.width {
+                ascii.push(self.icon_at(Point { row, col }, overlay, &stars));
+                // add whitespace for better looking board
+                ascii.push(padding.white());
+            }
+            ascii.push("\n".white());
+            // wide points get as tall, to keep the board square
+            for _ in 1..legend_max_char_width {
+                ascii.push(format!("{margin}┃\n").white());
             }
         }
 
         // Add connector
-        ascii.push(" ┗".white());
         // padding * number of stones, plus the 1 char for each stone
         let row_len = padding.len() * self.width + self.width;
-        for _ in 0..row_len {
-            // add lines for columns
-            ascii.push("━".white());
-        }
+        ascii.push(format!("{margin}┗{}\n", "━".repeat(row_len)).white());
 
-        // spacing before column legend
-        ascii.push("\n".white());
-        for _ in 0..(padding.len() + 2) {
-            ascii.push(" ".white());
+        // column legend, with each label ending under its column
+        ascii.push(format!("{margin} ").white());
+        for label in col_labels {
+            ascii.push(format!("{label:>width$}", width = legend_max_char_width + 1).white());
         }
+        ascii
+    }
+}
 
-        // column legend
-        let mut idx = 1;
-        let mut column_legend: Vec<ColoredString> = Vec::new();
-        let mut chunk = ascii.iter();
-        chunk.next(); // skip over the row legend markers (e.g. A|)
-        let mut skip_count = 0;
-        for val in chunk {
-            if val.contains('\n') {
-                break;
-            }
-            for char in val.chars() {
-                // adjust for n char long legend (e.g. '15' is 2 char long)
-                if skip_count > 0 {
-                    skip_count -= 1;
-                    continue;
-                }
-                if char != ' ' {
-                    column_legend.push(idx.to_string().white());
-                    idx += 1;
-                    skip_count = idx.to_string().len() - 1;
-                } else {
-                    column_legend.push(" ".white());
-                }
-            }
+/// Where the points of a board end up on the screen when `Board::to_ascii`
+/// draws it, counting lines and characters from its top left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Layout {
+    /// Width of the row legends, before the `┃`
+    row_label_width: usize,
+    /// Width of the widest column legend. Every point is this wide plus
+    /// one, and this many lines tall.
+    // legend_width examples:
+    // '5' is 1 char long
+    // '11' is 2 char long
+    // Each of these need different padding size
+    legend_width: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Layout {
+    pub(crate) fn new(board: &Board, notation: Notation) -> Self {
+        let row_label_width = (0..board.height)
+            .map(|row| notation.row_label(row, board.height).len())
+            .max()
+            .unwrap_or(1);
+        let legend_width = (0..board.width)
+            .map(|col| notation.col_label(col).len())
+            .max()
+            .unwrap_or(1);
+        Layout {
+            row_label_width,
+            legend_width,
+            width: board.width,
+            height: board.height,
         }
+    }
 
-        ascii.append(&mut column_legend); // add column_legend markers
-        ascii
+    /// The number of lines the board takes up, legends included.
+    pub(crate) fn lines(&self) -> usize {
+        self.height * self.legend_width + 2
+    }
+
+    /// The `(x, y)` of the point at `(row, col)`.
+    pub(crate) fn screen_pos(&self, (row, col): (usize, usize)) -> (usize, usize) {
+        let x = self.first_col() + col * (self.legend_width + 1);
+        (x, row * self.legend_width)
+    }
+
+    /// The `(row, col)` of the point drawn at or around `(x, y)`. Each point
+    /// owns the cells from the middle of the gap on its left to the middle
+    /// of the gap on its right, and the lines below it up to the next row.
+    pub(crate) fn point_at(&self, (x, y): (usize, usize)) -> Option<(usize, usize)> {
+        let row = y / self.legend_width;
+        let col =
+            (x + self.legend_width / 2).checked_sub(self.first_col())? / (self.legend_width + 1);
+        (row < self.height && col < self.width).then_some((row, col))
+    }
+
+    /// The `x` of the first column of points.
+    fn first_col(&self) -> usize {
+        self.row_label_width + 1 + self.legend_width
     }
 }
 
 impl Display for Board {
     /// Used for pretty printing of the Board object
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for msg in &self.to_ascii() {
+        for msg in &self.to_ascii(Notation::default(), &Overlay::new()) {
             write!(f, "{}", msg)?
         }
         Ok(())
@@ -158,33 +298,192 @@ impl Display for Board {
 mod tests {
 
     use super::*;
+    use crate::game_logic::overlay::Mark;
 
     #[test]
     fn new_board_add_stone() {
         let mut board = Board::new(9, 9);
         let black = Stone::Black;
         let white = Stone::White;
-        board.place_stone(&GameMove::new(black, (1, 0), 0));
-        board.place_stone(&GameMove::new(white, (0, 1), 0));
-        board.place_stone(&GameMove::new(black, (2, 1), 0));
-        board.place_stone(&GameMove::new(white, (1, 2), 0));
+        for (stone, (row, col)) in [
+            (black, (1, 0)),
+            (white, (0, 1)),
+            (black, (2, 1)),
+            (white, (1, 2)),
+        ] {
+            let point = board.point(row, col).unwrap();
+            board.place_stone(&GameMove::new(stone, point, 0));
+        }
         assert_eq!(black, board.stone_at(1, 0));
         assert_eq!(black, board.stone_at(2, 1));
         assert_eq!(white, board.stone_at(0, 1));
         assert_eq!(white, board.stone_at(1, 2));
     }
 
+    #[test]
+    fn stone_on_checks_the_point() {
+        let mut board = Board::new(3, 3);
+        let corner = board.point(2, 2).unwrap();
+        board.place_stone(&GameMove::new(Stone::Black, corner, 0));
+        assert_eq!(board.stone_on(corner), Ok(Stone::Black));
+        // a point of a larger board
+        let far = Board::new(4, 4).point(3, 0).unwrap();
+        assert_eq!(board.stone_on(far), Err(MoveError::OutOfBounds));
+    }
+
     #[test]
     fn place_stone_in_corner() {
         let mut board = Board::new(3, 3);
-        board.place_stone(&GameMove::new(Stone::Black, (2, 2), 0));
+        let corner = board.point(2, 2).unwrap();
+        board.place_stone(&GameMove::new(Stone::Black, corner, 0));
         assert_eq!(board.get_state()[2][2], Stone::Black);
     }
 
+    #[test]
+    fn index_of_pos_is_checked() {
+        let board = Board::new(3, 2);
+        assert_eq!(board.index_of_pos((1, 2)), Some(5));
+        assert_eq!(board.index_of_pos((0, 3)), None);
+        assert_eq!(board.index_of_pos((2, 0)), None);
+    }
+
+    #[test]
+    fn neighbors_stay_on_board() {
+        let board = Board::new(3, 2);
+        assert_eq!(board.neighbors((0, 0)), vec![(1, 0), (0, 1)]);
+        assert_eq!(board.neighbors((1, 2)), vec![(0, 2), (1, 1)]);
+    }
+
     #[test]
     fn update_board_with_corner_move() {
         let mut board = Board::new(3, 3);
-        board.place_stone(&GameMove::new(Stone::Black, (2, 2), 0));
+        let corner = board.point(2, 2).unwrap();
+        board.place_stone(&GameMove::new(Stone::Black, corner, 0));
         assert_eq!(board.get_state()[2][2], Stone::Black);
     }
+
+    #[test]
+    fn legends_follow_the_notation() {
+        colored::control::set_override(false);
+        let draw = |board: &Board, notation| -> String {
+            board
+                .to_ascii(notation, &Overlay::new())
+                .iter()
+                .map(|s| s.to_string())
+                .collect()
+        };
+        let board = Board::new(3, 3);
+        assert_eq!(
+            draw(&board, Notation::RowLetter),
+            "A┃ . . . \nB┃ . . . \nC┃ . . . \n ┗━━━━━━\n   1 2 3"
+        );
+        assert_eq!(
+            draw(&board, Notation::Gtp),
+            "3┃ . . . \n2┃ . . . \n1┃ . . . \n ┗━━━━━━\n   A B C"
+        );
+        let board = Board::new(12, 12);
+        let drawn = draw(&board, Notation::RowLetter);
+        assert!(drawn.starts_with("A┃  .  ."));
+        assert!(drawn.ends_with("  9 10 11 12"));
+        let drawn = draw(&board, Notation::Gtp);
+        assert!(drawn.starts_with("12┃ . ."));
+        assert!(drawn.contains("\n 1┃ . ."));
+        assert!(drawn.ends_with("   A B C D E F G H J K L M"));
+    }
+
+    #[test]
+    fn marks_and_star_points_are_drawn() {
+        colored::control::set_override(false);
+        let mut board = Board::new(9, 9);
+        let point = board.point(0, 1).unwrap();
+        board.place_stone(&GameMove::new(Stone::Black, point, 0));
+        let mut overlay = Overlay::new();
+        overlay.mark(point, Mark::LastMove);
+        overlay.mark(board.point(0, 2).unwrap(), Mark::Ko);
+        let drawn: String = board
+            .to_ascii(Notation::RowLetter, &overlay)
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let lines: Vec<&str> = drawn.lines().collect();
+        assert_eq!(lines[0], "A┃ . ◉ □ . . . . . . ");
+        assert_eq!(lines[2], "C┃ . . + . . . + . . ");
+        assert_eq!(lines[4], "E┃ . . . . + . . . . ");
+        // other sizes have no star points
+        let drawn = Board::new(5, 5).to_string();
+        assert!(!drawn.contains('+'));
+    }
+
+    #[test]
+    fn layout_matches_the_drawn_board() {
+        colored::control::set_override(false);
+        for (size, notation) in [
+            (9, Notation::RowLetter),
+            (12, Notation::Gtp),
+            (13, Notation::RowLetter),
+            (19, Notation::RowLetter),
+        ] {
+            let mut board = Board::new(size, size);
+            board.state[size - 1][size - 2] = Stone::Black;
+            let layout = Layout::new(&board, notation);
+            let drawn: String = board
+                .to_ascii(notation, &Overlay::new())
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+            let lines: Vec<&str> = drawn.lines().collect();
+            assert_eq!(lines.len(), layout.lines());
+            let (x, y) = layout.screen_pos((size - 1, size - 2));
+            let stone = lines[y].chars().nth(x);
+            assert_eq!(stone, Some('●'), "{size} {notation:?}");
+        }
+    }
+
+    #[test]
+    fn screen_cells_map_back_to_points() {
+        for (size, notation) in [
+            (9, Notation::RowLetter),
+            (13, Notation::RowLetter),
+            (19, Notation::Gtp),
+        ] {
+            let layout = Layout::new(&Board::new(size, size), notation);
+            for row in 0..size {
+                for col in 0..size {
+                    let (x, y) = layout.screen_pos((row, col));
+                    assert_eq!(layout.point_at((x, y)), Some((row, col)));
+                    // the cell just right of the point, and the line below
+                    assert_eq!(layout.point_at((x + 1, y)), Some((row, col)));
+                    let below = y + layout.legend_width - 1;
+                    assert_eq!(layout.point_at((x, below)), Some((row, col)));
+                }
+            }
+        }
+        // on 9x9 "A┃ . ." has the first point at x 3
+        let layout = Layout::new(&Board::new(9, 9), Notation::RowLetter);
+        assert_eq!(layout.point_at((0, 0)), None);
+        assert_eq!(layout.point_at((2, 0)), None);
+        assert_eq!(layout.point_at((3, 0)), Some((0, 0)));
+        assert_eq!(layout.point_at((5, 1)), Some((1, 1)));
+        assert_eq!(layout.point_at((3, 9)), None);
+        assert_eq!(layout.point_at((21, 0)), None);
+        // on 13x13 "A┃  .  ." gives each point a gap on both sides
+        let layout = Layout::new(&Board::new(13, 13), Notation::RowLetter);
+        assert_eq!(layout.point_at((3, 0)), Some((0, 0)));
+        assert_eq!(layout.point_at((2, 0)), None);
+        assert_eq!(layout.point_at((6, 3)), Some((1, 1)));
+    }
+
+    #[test]
+    fn points_stay_on_board() {
+        let board = Board::new(3, 2);
+        assert_eq!(board.point(1, 2).map(<(usize, usize)>::from), Ok((1, 2)));
+        assert_eq!(board.point(2, 0), Err(MoveError::OutOfBounds));
+        assert_eq!(board.point(0, 3), Err(MoveError::OutOfBounds));
+        for index in 0..6 {
+            let point = board.point_at(index).unwrap();
+            assert_eq!(board.index_of(point), index);
+        }
+        assert_eq!(board.point_at(6), None);
+        assert_eq!(Board::new(0, 0).point_at(0), None);
+    }
 }
@@ -0,0 +1,60 @@
+//! Errors for moves that the rules of the game do not allow, and for input
+//! that could not be understood.
+use std::fmt::Display;
+
+/// The reason a move or other action was refused by the game.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    /// The point already has a stone on it.
+    Occupied,
+    /// The point is not on the board.
+    OutOfBounds,
+    /// The move would leave its own chain without liberties while
+    /// capturing nothing, and the ruleset does not allow suicide.
+    Suicide,
+    /// The move retakes a ko immediately, recreating the previous position.
+    Ko,
+    /// The move recreates an earlier position, which superko forbids.
+    Superko,
+    /// The move was played by the color whose turn it is not.
+    NotYourTurn,
+    /// The move was played by `Stone::Empty`, which is no player's color.
+    NoColor,
+    /// The game has finished, so no more moves can be played.
+    GameOver,
+    /// There is no move to take back.
+    NothingToUndo,
+    /// There is no undone move to play again.
+    NothingToRedo,
+    /// The game tree has no such variation to move to.
+    NoVariation,
+    /// The point is past the 52 rows and columns a game record can name,
+    /// so the move could not be undone, redone or saved.
+    Unrecordable,
+    /// The input given for an action could not be understood.
+    ParseError { input: String, reason: String },
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveError::Occupied => write!(f, "The point already has a stone"),
+            MoveError::OutOfBounds => write!(f, "The point is not on the board"),
+            MoveError::Suicide => write!(f, "Suicide is not allowed"),
+            MoveError::Ko => write!(f, "Ko: the stone cannot be retaken immediately"),
+            MoveError::Superko => write!(f, "Superko: the position has already been played"),
+            MoveError::NotYourTurn => write!(f, "It is not that player's turn"),
+            MoveError::NoColor => write!(f, "Only Black and White can play"),
+            MoveError::GameOver => write!(f, "The game is over"),
+            MoveError::NothingToUndo => write!(f, "There is no move to undo"),
+            MoveError::NothingToRedo => write!(f, "There is no move to redo"),
+            MoveError::NoVariation => write!(f, "There is no such variation"),
+            MoveError::Unrecordable => {
+                write!(f, "The point is too far out to record in the game")
+            }
+            MoveError::ParseError { input, reason } => write!(f, "'{input}': {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
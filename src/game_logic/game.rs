@@ -1,21 +1,75 @@
 //! This module represents the game state and is where you can start to play the game.
-use anyhow::Result;
+use anyhow::{bail, Result};
 
-use crate::game_logic::{board::*, game_move::GameMove, stone::Stone};
+use crate::game_logic::{
+    board::*,
+    error::MoveError,
+    game_move::Play,
+    handicap::{fixed_handicap_points, Handicap},
+    overlay::Mark,
+    phase::{GamePhase, GameResult},
+    players::Players,
+    position::{Checkpoint, Position},
+    prisoners::Prisoners,
+    rules::Ruleset,
+    scoring::{self, Score, Territory},
+    stone::Stone,
+    stone_removal::StoneRemoval,
+};
+use crate::sgf::{self, GameTree, Markup, NodeId, SgfError};
 use crate::ui::*;
-use crate::union_find::UnionFind;
+use std::{cmp::Ordering, collections::HashSet, fs, path::Path};
 
 #[derive(Debug)]
 pub struct Game<UI> {
-    pub board: Board,
-    stone_groups: UnionFind,
-    // players: TODO
+    /// The stones on the board and whose turn it is, which the rules are
+    /// applied to
+    position: Position,
+    /// Komi set for this game, overriding the ruleset's default
+    komi: Option<f32>,
+    handicap: Option<Handicap>,
+    /// Handicap stones placed so far
+    handicap_stones: Vec<(usize, usize)>,
+    /// Free handicap stones Black still has to place before White moves
+    free_handicap_left: usize,
+    /// Every turn played on the way to the current node, in order
+    moves: Vec<Play>,
+    /// Every move and variation played or loaded, with their comments and markup
+    tree: GameTree,
+    /// The node of `tree` the board is at
+    current: NodeId,
+    /// The state of the game from before each node on the way from the
+    /// root of `tree` to `current`
+    undo_snapshots: Vec<Snapshot>,
+    /// Nodes taken back by `undo`, most recently undone last
+    redo_stack: Vec<NodeId>,
+    /// Set while a node already in `tree` is replayed, so its moves are
+    /// not added to the tree again
+    replaying: bool,
+    /// Names of the Black and White players, if known
+    player_names: (Option<String>, Option<String>),
     // timer: TODO
-    // board_history: TODO
-    pub(crate) turn: bool,
-    pub(crate) move_number: usize,
+    phase: GamePhase,
+    /// Dead stones marked during `GamePhase::Scoring`
+    stone_removal: StoneRemoval,
+    result: Option<GameResult>,
+    /// Set when the game is abandoned, which leaves it without a result
     game_over: bool,
     ui: UI,
+    /// Players of either color that do not play from `ui`, and the other
+    /// displays the game is shown on
+    players: Players,
+}
+
+/// The parts of a `Game` that a move changes, used to take back a move
+/// that turns out to break the rules once it has been played out, and to
+/// undo and redo moves.
+#[derive(Clone, Debug)]
+struct Snapshot {
+    position: Checkpoint,
+    phase: GamePhase,
+    /// Length of the move list
+    moves_len: usize,
 }
 
 impl<UI: UserInterface> Game<UI> {
@@ -31,287 +85,1725 @@ impl<UI: UserInterface> Game<UI> {
     /// This will create a game object with the default option, which
     /// in our case is the TUI UI.
     pub fn new_game(width: usize, height: usize, ui: UI) -> Self {
-        let board = Board::new(width, height);
-        let stone_groups = UnionFind::new(width * height);
         Game {
-            board,
-            stone_groups,
-            turn: true,
-            move_number: 0,
+            position: Position::new(width, height),
+            komi: None,
+            handicap: None,
+            handicap_stones: Vec::new(),
+            free_handicap_left: 0,
+            moves: Vec::new(),
+            tree: GameTree::default(),
+            current: GameTree::ROOT,
+            undo_snapshots: Vec::new(),
+            redo_stack: Vec::new(),
+            replaying: false,
+            player_names: (None, None),
+            phase: GamePhase::Playing,
+            stone_removal: StoneRemoval::default(),
+            result: None,
             game_over: false,
             ui,
+            players: Players::default(),
+        }
+    }
+
+    /// Play the game under the given ruleset instead of the default one.
+    ///
+    /// ```rust
+    /// use go::{game::Game, RawModeUi, Ruleset};
+    /// let game: Game<RawModeUi> =
+    ///     Game::new_game(9, 9, Default::default()).with_ruleset(Ruleset::aga());
+    /// ```
+    pub fn with_ruleset(mut self, ruleset: Ruleset) -> Self {
+        self.position = self.position.with_ruleset(ruleset);
+        self
+    }
+
+    /// Give White `komi` points, instead of the ruleset's default komi.
+    ///
+    /// ```rust
+    /// use go::{game::Game, RawModeUi};
+    /// let game: Game<RawModeUi> = Game::new_game(9, 9, Default::default()).with_komi(5.5);
+    /// assert_eq!(game.komi(), 5.5);
+    /// ```
+    pub fn with_komi(mut self, komi: f32) -> Self {
+        self.set_komi(komi);
+        self
+    }
+
+    /// Change the komi of this game, overriding the ruleset's default.
+    pub fn set_komi(&mut self, komi: f32) {
+        self.komi = Some(komi);
+    }
+
+    /// Give Black a handicap, which has to be done before any move is played.
+    ///
+    /// Fixed handicap stones are placed right away and White moves first.
+    /// With a free handicap Black places the stones with its first moves,
+    /// after which White moves.
+    ///
+    /// ```rust
+    /// use go::{game::Game, Handicap, RawModeUi, Stone};
+    /// let game: Game<RawModeUi> = Game::new_game(9, 9, Default::default())
+    ///     .with_handicap(Handicap::Fixed(2))
+    ///     .unwrap();
+    /// assert_eq!(game.board().get_state()[6][2], Stone::Black);
+    /// assert_eq!(game.komi(), 0.5);
+    /// ```
+    pub fn with_handicap(mut self, handicap: Handicap) -> Result<Self> {
+        if self.position.move_number() != 0 || self.handicap.is_some() {
+            bail!("Handicap has to be given before the game starts");
+        }
+        match handicap {
+            Handicap::Fixed(stones) => {
+                for (row, col) in
+                    fixed_handicap_points(self.board().width, self.board().height, stones)?
+                {
+                    let point = self.board().point(row, col)?;
+                    self.place_handicap_stone(point);
+                }
+                self.finish_handicap();
+            }
+            Handicap::Free(stones) => {
+                if !(2..self.board().width * self.board().height).contains(&stones) {
+                    bail!("Free handicap must be at least 2 stones and fit on the board");
+                }
+                self.free_handicap_left = stones;
+            }
         }
+        self.handicap = Some(handicap);
+        Ok(self)
+    }
+
+    /// Name the Black and White players, which is recorded in saved games.
+    pub fn with_player_names(mut self, black: &str, white: &str) -> Self {
+        self.player_names = (Some(black.to_string()), Some(white.to_string()));
+        self
+    }
+
+    /// Let `player` choose the moves of `stone`, instead of the game's UI.
+    /// The player is shown the game and told every move, like the UI is,
+    /// but only asked for input on its own turns.
+    ///
+    /// ```no_run
+    /// use go::{game::Game, gtp::GtpPlayer, RawModeUi, Stone};
+    /// let engine = GtpPlayer::spawn(Stone::White, "gnugo", &["--mode", "gtp"]).unwrap();
+    /// let mut game = Game::new_game(9, 9, RawModeUi::default()).with_player(Stone::White, engine);
+    /// game.start_game().unwrap();
+    /// ```
+    pub fn with_player(mut self, stone: Stone, player: impl Player + 'static) -> Self {
+        self.players.seat(stone, Box::new(player));
+        self
+    }
+
+    /// Show the game on `display` as well as on the game's UI. Displays
+    /// are never asked for input.
+    pub fn with_display(mut self, display: impl UserInterface + 'static) -> Self {
+        self.players.add_display(Box::new(display));
+        self
+    }
+
+    /// The handicap Black was given, if any.
+    pub fn handicap(&self) -> Option<Handicap> {
+        self.handicap
+    }
+
+    /// The handicap stones placed on the board so far.
+    pub fn handicap_stones(&self) -> &[(usize, usize)] {
+        &self.handicap_stones
+    }
+
+    /// The komi White receives in this game.
+    pub fn komi(&self) -> f32 {
+        let handicap_stones = self.handicap.map_or(0, |handicap| handicap.stones());
+        self.komi
+            .unwrap_or_else(|| self.position.ruleset().default_komi(handicap_stones))
+    }
+
+    /// Count the score of the board as it is now, using the ruleset's
+    /// scoring method. Stones marked dead are counted as prisoners, and
+    /// any handicap compensation is added to White's komi.
+    pub fn score(&self) -> Score {
+        let (board, prisoners) = self
+            .stone_removal
+            .apply(self.position.board(), self.position.prisoners());
+        let handicap_stones = self.handicap.map_or(0, |handicap| handicap.stones());
+        let komi = self.komi() + self.position.ruleset().compensation(handicap_stones);
+        scoring::score(&board, &prisoners, komi, self.position.ruleset().scoring)
+    }
+
+    /// Who each empty point of the board belongs to, with `None` for points
+    /// holding a stone, as it would be counted now. Stones marked dead are
+    /// taken off first, so UIs can shade the result of the marking.
+    pub fn territory(&self) -> Vec<Vec<Option<Territory>>> {
+        let (board, _) = self
+            .stone_removal
+            .apply(self.position.board(), self.position.prisoners());
+        scoring::classify_territory(&board, self.position.ruleset().scoring)
+    }
+
+    /// The stones marked dead during the stone removal phase.
+    pub fn dead_stones(&self) -> &HashSet<(usize, usize)> {
+        self.stone_removal.dead_stones()
     }
 
     /// Start the game associated with this object.
     /// Note: This assumes the game will be played like any standard
     /// game would be played.
+    ///
+    /// Returns once the game is abandoned or has a result. After both
+    /// players pass in a row they mark the dead stones, and once both
+    /// accept the marking the board is scored to decide the result.
     pub fn start_game(&mut self) -> Result<()> {
         loop {
             if self.game_over {
                 return Ok(());
             }
-            self.ui.view(&self.board)?;
-            self.update()?;
+            match self.phase {
+                GamePhase::Playing => {
+                    let view = self.view();
+                    self.ui.view(&view)?;
+                    self.players.view(&view)?;
+                    self.take_turn()?;
+                }
+                GamePhase::Scoring if self.stone_removal.both_accepted() => {
+                    let score = self.score();
+                    self.result = Some(score.result());
+                    self.phase = GamePhase::Finished;
+                    self.ui.show_score(&score)?;
+                    self.players.show_score(&score)?;
+                }
+                GamePhase::Scoring => {
+                    // show the board as it would be scored, without the dead stones
+                    let view = self.view();
+                    self.ui.view(&view)?;
+                    self.players.view(&view)?;
+                    self.take_turn()?;
+                }
+                GamePhase::Finished => return Ok(()),
+            }
+        }
+    }
+
+    /// Ask for the next action and carry it out. Actions the rules refuse,
+    /// and input that could not be understood, are shown to the player who
+    /// gave them, who can try again.
+    fn take_turn(&mut self) -> Result<()> {
+        let stone = self.current_stone();
+        let Err(err) = self.update() else {
+            return Ok(());
+        };
+        let Some(move_err) = err.downcast_ref::<MoveError>() else {
+            return Err(err);
+        };
+        if !self.players.show_error(stone, move_err)? {
+            self.ui.show_error(move_err)?;
+        }
+        Ok(())
+    }
+
+    /// The stage the game is in.
+    pub fn phase(&self) -> GamePhase {
+        self.phase
+    }
+
+    /// How the game ended, if it has finished.
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    /// The stones captured by each player so far.
+    pub fn prisoners(&self) -> &Prisoners {
+        self.position.prisoners()
+    }
+
+    /// Every turn played so far, in order. Undone turns are not included.
+    pub fn moves(&self) -> &[Play] {
+        &self.moves
+    }
+
+    /// Write the game as an SGF (FF[4]) record, with the board size, komi,
+    /// handicap stones, player names and the result once the game has
+    /// finished, followed by the whole game tree: every move and variation
+    /// with their comments and markup.
+    ///
+    /// ```rust
+    /// use go::{game::Game, RawModeUi};
+    /// let game: Game<RawModeUi> = Game::new_game(9, 9, Default::default()).with_komi(5.5);
+    /// assert!(game.to_sgf().starts_with("(;FF[4]GM[1]"));
+    /// ```
+    pub fn to_sgf(&self) -> String {
+        let mut tree = self.tree.clone();
+        let root = tree.node_mut(GameTree::ROOT);
+        let one = |value: String| vec![value];
+        root.set("FF", one("4".to_string()));
+        root.set("GM", one("1".to_string()));
+        root.set("CA", one("UTF-8".to_string()));
+        root.set("AP", one(format!("go:{}", env!("CARGO_PKG_VERSION"))));
+        root.set(
+            "SZ",
+            one(sgf::encode_size(self.board().width, self.board().height)),
+        );
+        root.set("KM", one(self.komi().to_string()));
+        if let Some(black) = &self.player_names.0 {
+            root.set("PB", one(black.clone()));
+        }
+        if let Some(white) = &self.player_names.1 {
+            root.set("PW", one(white.clone()));
+        }
+        if let Some(handicap) = self.handicap {
+            root.set("HA", one(handicap.stones().to_string()));
+        }
+        if !self.handicap_stones.is_empty() {
+            let points = self
+                .handicap_stones
+                .iter()
+                .filter_map(|&pos| sgf::encode_point(pos));
+            root.set("AB", points.collect());
+        }
+        if let Some(result) = self.result {
+            root.set("RE", one(result.to_string()));
+        }
+        tree.to_string()
+    }
+
+    /// Save the game to an SGF file at `path`, see `to_sgf`.
+    pub fn save_sgf(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_sgf())?;
+        Ok(())
+    }
+
+    /// Build a game from an SGF record, replaying its main line. Stones set
+    /// up with `AB` and `AW` are put on the board, and every move is played
+    /// through the rules of the game, so illegal records are refused.
+    ///
+    /// Variations off the main line are kept in the game tree and are only
+    /// checked once they are stepped into. The result of the record is not
+    /// applied, so a finished game can be looked through, or played on from
+    /// its last position.
+    ///
+    /// ```rust
+    /// use go::{game::Game, RawModeUi, Stone};
+    /// let game: Game<RawModeUi> =
+    ///     Game::from_sgf("(;SZ[9]KM[7];B[ee];W[cc])", Default::default()).unwrap();
+    /// assert_eq!(game.board().get_state()[4][4], Stone::Black);
+    /// assert_eq!(game.komi(), 7.0);
+    /// ```
+    pub fn from_sgf(text: &str, ui: UI) -> Result<Self> {
+        let tree = sgf::parse(text)?;
+        let root = tree.node(GameTree::ROOT).clone();
+        let error = |ident: &str, value: &str, reason: &str| SgfError::Property {
+            node: 0,
+            ident: ident.to_string(),
+            reason: format!("'{value}' {reason}"),
+        };
+        let value = |ident: &str| root.get(ident)?.first().map(String::as_str);
+
+        let (width, height) = match value("SZ") {
+            Some(size) => {
+                sgf::decode_size(size).ok_or_else(|| error("SZ", size, "is not a board size"))?
+            }
+            None => (19, 19),
+        };
+        let mut game = Game::new_game(width, height, ui);
+        if let Some(komi) = value("KM") {
+            let komi = komi
+                .trim()
+                .parse()
+                .map_err(|_| error("KM", komi, "is not a number"))?;
+            game = game.with_komi(komi);
+        }
+        game.player_names = (value("PB").map(String::from), value("PW").map(String::from));
+        if let Some(stones) = value("HA") {
+            let count: usize = stones
+                .trim()
+                .parse()
+                .map_err(|_| error("HA", stones, "is not a number of stones"))?;
+            if count >= 2 {
+                // recorded as fixed when the stones are on the usual star points
+                let mut added = Vec::new();
+                for point in root.get("AB").unwrap_or_default() {
+                    let points = game.points_on_board(point).unwrap_or_default();
+                    added.extend(points.into_iter().map(<(usize, usize)>::from));
+                }
+                added.sort();
+                let mut fixed = fixed_handicap_points(width, height, count).unwrap_or_default();
+                fixed.sort();
+                game.handicap = Some(if added == fixed {
+                    Handicap::Fixed(count)
+                } else {
+                    Handicap::Free(count)
+                });
+            }
+        }
+
+        game.replaying = true;
+        let replayed = game.replay_sgf_node(0, &root);
+        game.replaying = false;
+        replayed?;
+        game.tree = tree;
+        while !game.tree.children(game.current).is_empty() {
+            game.forward(0)?;
+        }
+        Ok(game)
+    }
+
+    /// Load a game from the SGF file at `path`, see `from_sgf`.
+    pub fn load_sgf(path: impl AsRef<Path>, ui: UI) -> Result<Self> {
+        Self::from_sgf(&fs::read_to_string(path)?, ui)
+    }
+
+    /// The color of the player whose turn it is.
+    pub fn to_move(&self) -> Stone {
+        self.current_stone()
+    }
+
+    /// The board as it is now.
+    pub fn board(&self) -> &Board {
+        self.position.board()
+    }
+
+    /// The position the game is in, which the rules are applied to.
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// The game as the UIs are shown it, annotated with the markup of the
+    /// current node. While scoring, the stones marked dead are off the board
+    /// and counted as prisoners, and they and the territory are annotated.
+    pub fn view(&self) -> GameView {
+        let mut view = GameView::from_position(&self.position);
+        if self.phase == GamePhase::Scoring {
+            (view.board, view.prisoners) = self
+                .stone_removal
+                .apply(self.position.board(), self.position.prisoners());
+            for (row, owners) in self.territory().iter().enumerate() {
+                for (col, owner) in owners.iter().enumerate() {
+                    let stone = match owner {
+                        Some(Territory::Black) => Stone::Black,
+                        Some(Territory::White) => Stone::White,
+                        _ => continue,
+                    };
+                    if let Ok(point) = self.position.board().point(row, col) {
+                        view.annotations.mark(point, Mark::Territory(stone));
+                    }
+                }
+            }
+            let board = self.position.board();
+            for &(row, col) in self.dead_stones() {
+                if let Ok(point) = board.point(row, col) {
+                    view.annotations
+                        .mark(point, Mark::Dead(board.state[row][col]));
+                }
+            }
+        }
+        view.annotations.add_markup(
+            &self.tree.node(self.current).markup(),
+            self.position.board(),
+        );
+        view.last_move = self.moves.last().copied();
+        view.phase = self.phase;
+        view.komi = self.komi();
+        view
+    }
+
+    /// Play a stone of color `stone` at `point`, or pass with `None`, even if
+    /// it is not that color's turn. This is for front ends that are told the
+    /// moves of both players, like GTP; the move is still checked by the
+    /// rules, and a move played after both players passed resumes play.
+    ///
+    /// While Black has free handicap stones left to place, only Black can
+    /// play, and its stones are placed as handicap stones.
+    pub fn play(&mut self, stone: Stone, point: Option<Point>) -> Result<()> {
+        if self.phase == GamePhase::Finished {
+            return Err(MoveError::GameOver.into());
+        }
+        if stone == Stone::Empty {
+            return Err(MoveError::NoColor.into());
+        }
+        if let Some(point) = point {
+            self.check_empty_point(point)?;
+        }
+        if self.free_handicap_left > 0 {
+            let (Stone::Black, Some(point)) = (stone, point) else {
+                return Err(MoveError::NotYourTurn.into());
+            };
+            return self.update_playing(UserAction::Move(point));
+        }
+        let before = (
+            self.current_stone(),
+            self.phase,
+            std::mem::take(&mut self.stone_removal),
+        );
+        self.position.set_to_move(stone);
+        self.phase = GamePhase::Playing;
+        let played = match point {
+            Some(point) => self.make_move(point),
+            None => {
+                self.pass();
+                Ok(())
+            }
+        };
+        if played.is_err() {
+            self.position.set_to_move(before.0);
+            self.phase = before.1;
+            self.stone_removal = before.2;
+        }
+        played
+    }
+
+    /// The tree of every move and variation played or loaded, with their
+    /// comments and markup.
+    pub fn tree(&self) -> &GameTree {
+        &self.tree
+    }
+
+    /// The node of the game tree the board is at.
+    pub fn current_node(&self) -> NodeId {
+        self.current
+    }
+
+    /// Comment on the current node, replacing any earlier comment.
+    pub fn set_comment(&mut self, comment: &str) {
+        self.tree.node_mut(self.current).set_comment(comment);
+    }
+
+    /// Draw `markup` on the board at the current node.
+    pub fn add_markup(&mut self, markup: Markup) {
+        self.tree.node_mut(self.current).add_markup(markup);
+    }
+
+    /// Step forward into one of the nodes following the current one, 0 being
+    /// the main line, playing its moves and setup through the rules.
+    pub fn forward(&mut self, variation: usize) -> Result<()> {
+        let Some(&child) = self.tree.children(self.current).get(variation) else {
+            return Err(MoveError::NoVariation.into());
+        };
+        self.enter(child)
+    }
+
+    /// Step back to the node before the current one, restoring the board,
+    /// chains, prisoners and turn to exactly how they were there. Stepping
+    /// back over the pass that ended play goes back to playing, dropping any
+    /// dead stone marks.
+    pub fn back(&mut self) -> Result<()> {
+        let (Some(parent), Some(before)) =
+            (self.tree.parent(self.current), self.undo_snapshots.pop())
+        else {
+            return Err(MoveError::NothingToUndo.into());
+        };
+        self.restore(before);
+        self.current = parent;
+        self.stone_removal = StoneRemoval::default();
+        Ok(())
+    }
+
+    /// Move across to the next variation of the current node.
+    pub fn next_variation(&mut self) -> Result<()> {
+        self.switch_variation(1)
+    }
+
+    /// Move across to the previous variation of the current node.
+    pub fn previous_variation(&mut self) -> Result<()> {
+        self.switch_variation(-1)
+    }
+
+    /// Go to any node of the game tree, stepping back to where its line
+    /// branches off the current one and forward from there.
+    pub fn go_to(&mut self, node: NodeId) -> Result<()> {
+        let path = self.tree.path_to(node);
+        while !path.contains(&self.current) {
+            self.back()?;
+        }
+        let start = path.iter().position(|&id| id == self.current).unwrap();
+        for &id in &path[start + 1..] {
+            self.enter(id)?;
+        }
+        Ok(())
+    }
+
+    /// Take back the last turn, see `back`. The turn stays in the game
+    /// tree, and can be played again with `redo`.
+    pub fn undo(&mut self) -> Result<()> {
+        let left = self.current;
+        self.back()?;
+        self.redo_stack.push(left);
+        Ok(())
+    }
+
+    /// Play the most recently undone turn again. Any new turn played after
+    /// an undo clears the turns that could be redone.
+    pub fn redo(&mut self) -> Result<()> {
+        match self.redo_stack.pop() {
+            Some(node) if self.tree.parent(node) == Some(self.current) => self.enter(node),
+            _ => {
+                self.redo_stack.clear();
+                Err(MoveError::NothingToRedo.into())
+            }
         }
     }
 
+    /// Move to the sibling of the current node `offset` places away.
+    fn switch_variation(&mut self, offset: isize) -> Result<()> {
+        let sibling = self.tree.parent(self.current).and_then(|parent| {
+            let siblings = self.tree.children(parent);
+            let here = siblings.iter().position(|&id| id == self.current)?;
+            siblings.get(here.checked_add_signed(offset)?).copied()
+        });
+        let Some(sibling) = sibling else {
+            return Err(MoveError::NoVariation.into());
+        };
+        let here = self.current;
+        self.back()?;
+        if let Err(err) = self.enter(sibling) {
+            // the variation breaks the rules, so stay on this one
+            self.enter(here)?;
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Replay node `id` of the game tree, which follows the current node,
+    /// and make it the current node. If the rules refuse the node, the game
+    /// stays where it was.
+    fn enter(&mut self, id: NodeId) -> Result<()> {
+        let before = self.snapshot();
+        let node = self.tree.node(id).clone();
+        self.replaying = true;
+        let replayed = self.replay_sgf_node(self.undo_snapshots.len() + 1, &node);
+        self.replaying = false;
+        if let Err(err) = replayed {
+            self.restore(before);
+            return Err(err);
+        }
+        self.current = id;
+        self.undo_snapshots.push(before);
+        Ok(())
+    }
+
     /// This function updates the game based on User actions.
     /// Abstractoin from the UI trait. With OGS(todo), Opponent actions will be listened for
+    ///
+    /// The UI, players and displays hear whose turn it is before input is
+    /// asked for, and which turns were played or taken back after. Input
+    /// comes from the player of the color to move, or from the UI if that
+    /// color has no player.
     fn update(&mut self) -> Result<()> {
-        match self.ui.input()? {
-            UserAction::Move(row, col) => self.make_move(row, col),
+        let stone = self.current_stone();
+        self.notify(&GameEvent::ToMove {
+            stone,
+            phase: self.phase,
+        })?;
+        let board = self.position.board();
+        let action = match self.players.player(stone) {
+            Some(player) => player.next_action(board)?,
+            None => self.ui.input(board)?,
+        };
+        let (moves_before, last_move) = (self.moves.len(), self.moves.last().copied());
+        let updated = if self.phase == GamePhase::Scoring {
+            self.update_stone_removal(action)
+        } else {
+            self.update_playing(action)
+        };
+        match self.moves.len().cmp(&moves_before) {
+            Ordering::Greater => self.notify(&GameEvent::Played(self.moves[moves_before]))?,
+            Ordering::Less => {
+                if let Some(play) = last_move {
+                    self.notify(&GameEvent::Undone(play))?;
+                }
+            }
+            Ordering::Equal => {}
+        }
+        updated
+    }
+
+    /// Tell the UI and everyone attached to the game about `event`.
+    fn notify(&mut self, event: &GameEvent) -> Result<()> {
+        self.ui.notify(event)?;
+        self.players.notify(event)
+    }
+
+    /// Handles user actions while the players take turns placing stones.
+    fn update_playing(&mut self, action: UserAction) -> Result<()> {
+        match action {
+            UserAction::Move(point) if self.free_handicap_left > 0 => {
+                self.check_empty_point(point)?;
+                self.place_handicap_stone(point);
+                self.free_handicap_left -= 1;
+                if self.free_handicap_left == 0 {
+                    self.finish_handicap();
+                }
+                Ok(())
+            }
+            UserAction::Pass if self.free_handicap_left > 0 => Ok(()),
+            UserAction::Move(point) => self.make_move(point),
+            UserAction::Pass => {
+                self.pass();
+                Ok(())
+            }
+            UserAction::Resign => {
+                self.resign();
+                Ok(())
+            }
+            UserAction::Undo => self.undo(),
+            UserAction::Redo => self.redo(),
             UserAction::Quit => {
                 self.game_over = true;
                 Ok(())
             }
-            UserAction::Noop => Ok(()),
+            UserAction::ToggleDead(..) | UserAction::Accept | UserAction::Noop => Ok(()),
         }
     }
 
-    /// Strictly checkes that you are not placing a stone on an existing stone
-    /// This should also check that row and column are within bounds
-    fn is_valid_move(&mut self, row: usize, col: usize) -> bool {
-        self.board.state[row][col] == Stone::Empty
-    }
-
-    /// This function checks the local "neighbors" and dispatches events
-    /// based on different cases.
-    ///
-    /// Cases:
-    /// - If there is a neighboring stone of the same color, we need to
-    /// combine the two stones since they are a connected "chain".
-    /// - If there is a neighboring stone of the opposing color, we need
-    /// to remove the placed stone's position from the opposing stones'
-    /// liberties list. Thereby needing to check for a capture.
+    /// Handles user actions while the players mark dead stones. Choosing a
+    /// point, either with `ToggleDead` or `Move`, marks its chain.
     ///
-    fn update_board(&mut self, mv: (usize, usize), stone: Stone) {
-        let (row, col) = mv;
-        let manhattan_adjacencies = [
-            (row.checked_sub(1), col.checked_mul(1)),
-            (row.checked_add(1), col.checked_mul(1)),
-            (row.checked_mul(1), col.checked_sub(1)),
-            (row.checked_mul(1), col.checked_add(1)),
-        ];
-        for (r, c) in manhattan_adjacencies {
-            if let (Some(adj_row), Some(adj_col)) = (r, c) {
-                if adj_row >= self.board.width || adj_col >= self.board.height {
-                    continue; // skip if out of bounds
+    /// Acceptance is asked of each player in turn, and both have to accept
+    /// the same marking before the game is scored.
+    fn update_stone_removal(&mut self, action: UserAction) -> Result<()> {
+        match action {
+            UserAction::ToggleDead(point) | UserAction::Move(point) => {
+                if self.board().stone_on(point)? != Stone::Empty {
+                    let chain = self.position.chain_at(point);
+                    self.stone_removal.toggle_chain(chain);
                 }
+            }
+            UserAction::Accept => {
+                let stone = self.current_stone();
+                self.stone_removal.accept(stone);
+                self.position.set_to_move(stone.get_opponent().unwrap());
+            }
+            UserAction::Resign => self.resign(),
+            UserAction::Undo => self.undo()?,
+            UserAction::Redo => self.redo()?,
+            UserAction::Quit => self.game_over = true,
+            UserAction::Pass | UserAction::Noop => {}
+        }
+        Ok(())
+    }
 
-                let move_index = self.board.index_of_pos(mv);
-                let adjacent_index = self.board.index_of_pos((adj_row, adj_col));
-                let existing_stone = self.board.state[adj_row][adj_col];
-
-                if existing_stone == stone {
-                    // union update stuff
-                    self.stone_groups.union(move_index, adjacent_index);
-                } else if existing_stone == stone.get_opponent().unwrap() {
-                    // remove current move position from this adjacent stones representative chain
-                    self.stone_groups
-                        .remove_liberty_from_chain(adjacent_index, move_index);
-
-                    dbg!("\nHERE\n{}", self.stone_groups.no_liberties(adjacent_index));
-                    if self.stone_groups.no_liberties(adjacent_index) {
-                        // stone here is dead. Update board
-                        // TODO: need to update a prisoners list or something for captures
-                        self.board.state[adj_row][adj_col] = Stone::Empty;
+    /// Put a Black handicap stone on the board. Handicap stones are not moves,
+    /// so they are not numbered and do not change whose turn it is.
+    fn place_handicap_stone(&mut self, point: Point) {
+        self.place_setup_stone(Stone::Black, point);
+        self.handicap_stones.push(point.into());
+    }
+
+    /// Put a stone on the board without playing it as a move, like the
+    /// stones an SGF record sets up with `AB` and `AW`.
+    fn place_setup_stone(&mut self, stone: Stone, point: Point) {
+        self.position.add_stone(stone, point);
+    }
+
+    /// Replay the properties of node number `number` of an SGF record that
+    /// set up stones, say who is to play, or play a move.
+    fn replay_sgf_node(&mut self, number: usize, node: &sgf::Node) -> Result<()> {
+        let error = |ident: &str, reason: String| SgfError::Property {
+            node: number,
+            ident: ident.to_string(),
+            reason,
+        };
+        if node.get("AE").is_some() {
+            return Err(error("AE", "removing stones is not supported".to_string()).into());
+        }
+
+        let mut setup = false;
+        for (ident, stone) in [("AB", Stone::Black), ("AW", Stone::White)] {
+            for value in node.get(ident).unwrap_or_default() {
+                let points = self.points_on_board(value).ok_or_else(|| {
+                    error(ident, format!("'{value}' is not a point on the board"))
+                })?;
+                for point in points {
+                    if self.board().state[point.row()][point.col()] != Stone::Empty {
+                        return Err(error(ident, format!("'{value}' already has a stone")).into());
+                    }
+                    // stones added to the root of a handicap game are its handicap
+                    if stone == Stone::Black && number == 0 && self.handicap.is_some() {
+                        self.place_handicap_stone(point);
+                    } else {
+                        self.place_setup_stone(stone, point);
                     }
+                    setup = true;
                 }
             }
         }
+        if number == 0 && !self.handicap_stones.is_empty() {
+            self.finish_handicap();
+        }
+        if let Some(player) = node.get("PL").and_then(<[String]>::first) {
+            let stone = match player.as_str() {
+                "B" => Stone::Black,
+                "W" => Stone::White,
+                other => return Err(error("PL", format!("'{other}' is not a color")).into()),
+            };
+            self.position.set_to_move(stone);
+        }
+        if number == 0 && setup {
+            // the position was set up rather than played, so it starts the history
+            self.position.restart_history();
+        } else if setup {
+            self.position.remember();
+        }
+
+        for (ident, stone) in [("B", Stone::Black), ("W", Stone::White)] {
+            let Some(values) = node.get(ident) else {
+                continue;
+            };
+            let Some(value) = values.first().map(String::as_str) else {
+                return Err(error(ident, "the move has no value".to_string()).into());
+            };
+            // SGF lets either color move, even twice in a row
+            self.position.set_to_move(stone);
+            let legacy_pass =
+                value == "tt" && self.board().width <= 19 && self.board().height <= 19;
+            if value.is_empty() || legacy_pass {
+                self.pass();
+                continue;
+            }
+            let point = sgf::decode_point(value)
+                .and_then(|(row, col)| self.board().point(row, col).ok())
+                .ok_or_else(|| error(ident, format!("'{value}' is not a point on the board")))?;
+            if self.board().state[point.row()][point.col()] != Stone::Empty {
+                return Err(error(ident, format!("'{value}' already has a stone")).into());
+            }
+            self.make_move(point)
+                .map_err(|err| error(ident, err.to_string()))?;
+        }
+        Ok(())
     }
 
-    /// This is a helper function that is in charge of updating the game
-    /// based on a move given by a player.
-    fn make_move(&mut self, row: usize, col: usize) -> Result<()> {
-        if !self.is_valid_move(row, col) {
-            return Ok(());
+    /// The points an SGF point list value stands for, if they are all on the board.
+    fn points_on_board(&self, value: &str) -> Option<Vec<Point>> {
+        sgf::decode_point_list(value)?
+            .into_iter()
+            .map(|(row, col)| self.board().point(row, col).ok())
+            .collect()
+    }
+
+    /// All handicap stones are down, so the game starts from this position
+    /// with White to move.
+    fn finish_handicap(&mut self) {
+        self.position.set_to_move(Stone::White);
+        self.position.restart_history();
+    }
+
+    /// The color of the player whose turn it is.
+    fn current_stone(&self) -> Stone {
+        self.position.to_move()
+    }
+
+    /// The current player passes their turn. Once the ruleset says the
+    /// passes in a row end play, the game moves on to scoring.
+    fn pass(&mut self) {
+        let snapshot = self.snapshot();
+        // the player to move is always Black or White
+        let outcome = self.position.pass(self.current_stone()).unwrap();
+        if outcome.ends_play {
+            self.phase = GamePhase::Scoring;
+        }
+        self.record_play(outcome.play, String::new(), snapshot);
+    }
+
+    /// Add a turn that was just played to the list of moves, and to the game
+    /// tree as a node after the current one, with `point` as its SGF value
+    /// (empty for a pass) and the state from before it so that it can be
+    /// undone. A turn that is already a variation of the current node
+    /// follows that variation instead.
+    fn record_play(&mut self, play: Play, point: String, before: Snapshot) {
+        self.moves.push(play);
+        if self.replaying {
+            return;
         }
-        let stn = if self.turn {
-            Stone::Black
+        let ident = if play.stone() == Stone::Black {
+            "B"
         } else {
-            Stone::White
+            "W"
         };
-        self.move_number += 1;
-        let mv = GameMove::new(stn, (row, col), self.move_number);
-        self.create_libs(mv.pos);
-        self.update_board(mv.pos, mv.stone);
-        self.board.place_stone(&mv);
-        self.turn = !self.turn;
-        Ok(())
+        let existing = self
+            .tree
+            .children(self.current)
+            .iter()
+            .copied()
+            .find(|&child| self.tree.node(child).get(ident) == Some(std::slice::from_ref(&point)));
+        self.current = existing.unwrap_or_else(|| {
+            let mut node = sgf::Node::default();
+            node.push(ident, vec![point]);
+            self.tree.add_child(self.current, node)
+        });
+        self.undo_snapshots.push(before);
+        self.redo_stack.clear();
     }
 
-    /// This is a helper function for the self.make_move function to initialize
-    /// the liberties for the stone being placed.
-    ///
-    /// We can assume that the move is valid due to make_move checking for validity
-    fn create_libs(&mut self, pos: (usize, usize)) {
-        let (row, col) = pos;
-        let manhattan_adjacencies = [
-            (row.checked_sub(1), col.checked_mul(1)),
-            (row.checked_add(1), col.checked_mul(1)),
-            (row.checked_mul(1), col.checked_sub(1)),
-            (row.checked_mul(1), col.checked_add(1)),
-        ];
+    /// The current player resigns, giving the game to their opponent.
+    fn resign(&mut self) {
+        let winner = self.current_stone().get_opponent().unwrap();
+        self.result = Some(GameResult::Resignation { winner });
+        self.phase = GamePhase::Finished;
+    }
 
-        let mut libs: Vec<usize> = Vec::with_capacity(4);
+    /// Checks that the point is on this game's board, and has no stone on
+    /// it yet.
+    fn check_empty_point(&self, point: Point) -> Result<(), MoveError> {
+        if self.board().stone_on(point)? != Stone::Empty {
+            return Err(MoveError::Occupied);
+        }
+        Ok(())
+    }
 
-        for (r, c) in manhattan_adjacencies {
-            if let (Some(adj_row), Some(adj_col)) = (r, c) {
-                if adj_row >= self.board.width || adj_col >= self.board.height {
-                    continue; // skip if out of bounds
-                }
-                libs.push(self.board.index_of_pos((adj_row, adj_col)));
-            }
+    /// This is a helper function that is in charge of updating the game
+    /// based on a move given by a player.
+    fn make_move(&mut self, point: Point) -> Result<()> {
+        // a move the game tree cannot hold would be lost by undo and redo
+        let value = sgf::encode_point(point.into()).ok_or(MoveError::Unrecordable)?;
+        let snapshot = self.snapshot();
+        let outcome = self.position.play(self.current_stone(), point)?;
+        self.record_play(outcome.play, value, snapshot);
+        Ok(())
+    }
+
+    /// Copy everything that playing a move changes.
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            position: self.position.checkpoint(),
+            phase: self.phase,
+            moves_len: self.moves.len(),
         }
-        self.stone_groups
-            .initialize_liberties_of_pos(self.board.index_of_pos(pos), libs);
+    }
+
+    /// Put the game back to how it was when `snapshot` was taken.
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.position.rewind(snapshot.position);
+        self.phase = snapshot.phase;
+        self.moves.truncate(snapshot.moves_len);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game_logic::game_move::GameMove;
+    use crate::game_logic::{rules::KoRule, scoring::ScoringMethod};
     use std::{collections::HashSet, io::*};
 
     fn setup_game(input: &str) -> Game<TextUi<impl Read, impl Write>> {
         let reader = Cursor::new(String::from(input));
         let writer: Vec<u8> = vec![];
-        let ui = TextUi::new(reader, writer);
-        Game::new_game(9, 9, ui)
+        Game::new_game(9, 9, TextUi::new(reader, writer))
+    }
+
+    /// The point at `row` and `col` of the game's board.
+    fn at<UI: UserInterface>(game: &Game<UI>, row: usize, col: usize) -> Point {
+        game.board().point(row, col).unwrap()
+    }
+
+    #[test]
+    fn refused_play_keeps_the_dead_stones() {
+        let mut game = setup_game("a2\ni9\nb1\npass\npass\nmark i9\nq\n");
+        game.start_game().unwrap();
+        assert_eq!(game.phase(), GamePhase::Scoring);
+        let dead = game.dead_stones().clone();
+        assert!(dead.contains(&(8, 8)));
+        // White playing a1 would be suicide
+        let a1 = at(&game, 0, 0);
+        game.play(Stone::White, Some(a1)).unwrap_err();
+        assert_eq!(game.phase(), GamePhase::Scoring);
+        assert_eq!(game.dead_stones(), &dead);
     }
 
     #[test]
     fn make_move() {
         let mut game = setup_game("a1\n");
         game.update().unwrap();
-        assert_eq!(Stone::Black, game.board.stone_at(0, 0));
+        assert_eq!(Stone::Black, game.board().stone_at(0, 0));
     }
 
     #[test]
-    fn make_two_moves() {
-        let mut game = setup_game("a1\nb2\n");
+    fn players_choose_the_moves_of_their_color() {
+        let white = TextUi::new(Cursor::new("a1\ne5\nf6\n"), vec![]);
+        let mut game = setup_game("a1\nb2\nq\n").with_player(Stone::White, white);
+        game.start_game().unwrap();
+        // White's a1 is refused, and White tries again without Black moving
+        assert_eq!(game.moves().len(), 4);
+        assert_eq!(Stone::Black, game.board().stone_at(0, 0));
+        assert_eq!(Stone::White, game.board().stone_at(4, 4));
+        assert_eq!(Stone::Black, game.board().stone_at(1, 1));
+        assert_eq!(Stone::White, game.board().stone_at(5, 5));
+    }
+
+    #[test]
+    fn two_passes_score_the_game() {
+        let mut game = setup_game("a1\npass\nb2\npass\npass\naccept\naccept\n");
+        game.start_game().unwrap();
+        assert_eq!(game.phase(), GamePhase::Finished);
+        // Black owns the whole board, less White's 6.5 komi
+        assert_eq!(
+            game.result(),
+            Some(GameResult::Score {
+                winner: Stone::Black,
+                margin: 79.0 - 6.5
+            })
+        );
+        assert_eq!(game.position.move_number(), 5);
+        assert_eq!(Stone::Black, game.board().stone_at(1, 1));
+    }
+
+    #[test]
+    fn dead_stones_are_removed_before_scoring() {
+        let mut game = setup_game("a1\na2\npass\npass\nmark a1\naccept\naccept\n");
+        game.start_game().unwrap();
+        assert_eq!(game.phase(), GamePhase::Finished);
+        assert!(game.dead_stones().contains(&(0, 0)));
+        // the dead stone is still on the board, just not counted
+        assert_eq!(Stone::Black, game.board().stone_at(0, 0));
+        let score = game.score();
+        assert_eq!(score.white.captures, 1);
+        assert_eq!(score.white.territory, 80);
+        assert_eq!(
+            game.result(),
+            Some(GameResult::Score {
+                winner: Stone::White,
+                margin: 81.0 + 6.5
+            })
+        );
+    }
+
+    #[test]
+    fn view_follows_the_game() {
+        let mut game = setup_game("a1\nb2\npass\npass\nmark a1\nq\n");
+        let view = game.view();
+        assert_eq!(
+            (view.to_move, view.move_number, view.last_move),
+            (Stone::Black, 0, None)
+        );
+        game.start_game().unwrap();
+        let view = game.view();
+        assert_eq!(view.phase, GamePhase::Scoring);
+        assert_eq!(view.move_number, 4);
+        assert_eq!(
+            view.last_move,
+            Some(Play::Pass {
+                stone: Stone::White,
+                move_number: 4
+            })
+        );
+        // the stone marked dead is shown off the board, as a prisoner
+        assert_eq!(view.board.stone_at(0, 0), Stone::Empty);
+        assert_eq!(view.prisoners.captured_by(Stone::White), 1);
+        assert_eq!(view.komi, 6.5);
+        assert_eq!(view.ko, None);
+        // and annotated, with the territory it leaves
+        assert_eq!(
+            view.annotations.get(at(&game, 0, 0)),
+            Some(&Mark::Dead(Stone::Black))
+        );
+        assert_eq!(
+            view.annotations.get(at(&game, 8, 8)),
+            Some(&Mark::Territory(Stone::White))
+        );
+        game.add_markup(Markup::Triangle((1, 1)));
+        assert_eq!(
+            game.view().annotations.get(at(&game, 1, 1)),
+            Some(&Mark::Triangle)
+        );
+    }
+
+    #[test]
+    fn territory_after_marking() {
+        let mut game = setup_game("a1\na2\npass\npass\nmark a1\nq\n");
+        game.start_game().unwrap();
+        let territory = game.territory();
+        assert_eq!(territory[0][0], Some(Territory::White));
+        assert_eq!(territory[0][1], None);
+        assert_eq!(territory[8][8], Some(Territory::White));
+    }
+
+    #[test]
+    fn changing_marks_needs_new_acceptance() {
+        // Black accepts before the chain is unmarked again, so White
+        // accepting afterwards is not enough to score the game
+        let mut game = setup_game("a1\na2\npass\npass\nmark a2\naccept\nmark a2\naccept\nq\n");
+        game.start_game().unwrap();
+        assert_eq!(game.phase(), GamePhase::Scoring);
+        assert!(game.dead_stones().is_empty());
+    }
+
+    #[test]
+    fn whole_chain_is_marked() {
+        let mut game = setup_game("a1\nc1\na2\npass\npass\nmark a2\nq\n");
+        game.start_game().unwrap();
+        assert_eq!(game.dead_stones(), &HashSet::from([(0, 0), (0, 1)]));
+    }
+
+    #[test]
+    fn fixed_handicap_white_moves_first() {
+        let mut game = setup_game("e5\n")
+            .with_handicap(Handicap::Fixed(4))
+            .unwrap();
+        assert_eq!(game.handicap_stones().len(), 4);
+        assert_eq!(Stone::Black, game.board().stone_at(2, 2));
+        assert_eq!(Stone::Black, game.board().stone_at(6, 6));
+        assert_eq!(game.komi(), 0.5);
+        game.update().unwrap();
+        assert_eq!(Stone::White, game.board().stone_at(4, 4));
+        assert_eq!(game.position.move_number(), 1);
+    }
+
+    #[test]
+    fn free_handicap_black_places_stones_first() {
+        let mut game = setup_game("a1\nb1\nc1\nc2\n")
+            .with_handicap(Handicap::Free(3))
+            .unwrap();
+        for _ in 0..4 {
+            game.update().unwrap();
+        }
+        assert_eq!(game.handicap_stones(), &[(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(Stone::Black, game.board().stone_at(2, 0));
+        assert_eq!(Stone::White, game.board().stone_at(2, 1));
+        assert_eq!(game.position.move_number(), 1);
+    }
+
+    #[test]
+    fn play_waits_for_free_handicap_stones() {
+        let mut game = setup_game("").with_handicap(Handicap::Free(2)).unwrap();
+        game.view();
+        let a1 = at(&game, 0, 0);
+        let err = game.play(Stone::White, Some(a1)).unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&MoveError::NotYourTurn));
+        let err = game.play(Stone::Black, None).unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&MoveError::NotYourTurn));
+        game.play(Stone::Black, Some(a1)).unwrap();
+        assert_eq!(game.handicap_stones(), &[(0, 0)]);
+    }
+
+    #[test]
+    fn handicap_after_start_errors() {
+        let mut game = setup_game("a1\n");
         game.update().unwrap();
+        assert!(game.with_handicap(Handicap::Fixed(2)).is_err());
+        assert!(setup_game("").with_handicap(Handicap::Free(1)).is_err());
+        assert!(setup_game("").with_handicap(Handicap::Fixed(10)).is_err());
+    }
+
+    #[test]
+    fn aga_white_has_to_pass_last() {
+        let mut game = setup_game("a1\npass\npass\npass\nq\n").with_ruleset(Ruleset::aga());
+        for _ in 0..3 {
+            game.update().unwrap();
+            assert_eq!(game.phase(), GamePhase::Playing);
+        }
         game.update().unwrap();
-        assert_eq!(Stone::Black, game.board.stone_at(0, 0));
-        assert_eq!(Stone::White, game.board.stone_at(1, 1));
+        assert_eq!(game.phase(), GamePhase::Scoring);
+        // two passes from White and one from Black
+        assert_eq!(game.prisoners().captured_by(Stone::Black), 2);
+        assert_eq!(game.prisoners().captured_by(Stone::White), 1);
     }
 
     #[test]
-    fn make_two_connecting_moves() {
-        // let mut game = setup_game("a1\nb2\n");
-        let mut game: Game<RawModeUi> = Game::new_game(5, 5, Default::default());
-        game.make_move(0, 0).unwrap();
-        game.turn = !game.turn;
-        game.make_move(0, 1).unwrap();
-        assert_eq!(Stone::Black, game.board.stone_at(0, 0));
-        assert_eq!(Stone::Black, game.board.stone_at(0, 1));
-        assert!(game.stone_groups.connected(
-            game.board.index_of_pos((0, 0)),
-            game.board.index_of_pos((0, 1)),
-        ));
+    fn handicap_compensation_is_added_to_komi() {
+        let game = setup_game("")
+            .with_ruleset(Ruleset::chinese())
+            .with_handicap(Handicap::Fixed(3))
+            .unwrap();
+        assert_eq!(game.komi(), 0.5);
+        let score = game.score();
+        assert_eq!(score.white.komi, 3.5);
+        // Black's three stones own the whole board
+        assert_eq!(score.black.total, 81.0);
     }
 
     #[test]
-    fn merge_two_chains_together() {
-        // let mut game = setup_game("a1\nb2\n");
-        let mut game: Game<RawModeUi> = Game::new_game(5, 5, Default::default());
-        game.make_move(0, 0).unwrap();
-        game.turn = !game.turn;
-        game.make_move(0, 2).unwrap();
-        game.turn = !game.turn;
-        game.make_move(0, 1).unwrap();
-        assert_eq!(Stone::Black, game.board.stone_at(0, 0));
-        assert_eq!(Stone::Black, game.board.stone_at(0, 1));
-        assert_eq!(Stone::Black, game.board.stone_at(0, 2));
-        assert!(game.stone_groups.connected(
-            game.board.index_of_pos((0, 0)),
-            game.board.index_of_pos((0, 1)),
-        ));
-        assert!(game.stone_groups.connected(
-            game.board.index_of_pos((0, 0)),
-            game.board.index_of_pos((0, 2)),
+    fn undo_restores_captures() {
+        let mut game: Game<RawModeUi> = Game::new_game(3, 3, Default::default());
+        play_moves(&mut game, &[(0, 0), (0, 1), (2, 2), (1, 0)]);
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 0));
+        assert_eq!(game.moves().len(), 4);
+
+        game.undo().unwrap();
+        assert_eq!(Stone::Black, game.board().stone_at(0, 0));
+        assert_eq!(Stone::Empty, game.board().stone_at(1, 0));
+        assert_eq!(game.prisoners().captured_by(Stone::White), 0);
+        assert_eq!(game.moves().len(), 3);
+        assert_eq!(game.position.move_number(), 3);
+        assert_eq!(game.to_move(), Stone::White);
+
+        // the chains and liberties are back too, so the capture works again
+        game.make_move(at(&game, 1, 0)).unwrap();
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 0));
+        assert_eq!(game.prisoners().captured_by(Stone::White), 1);
+    }
+
+    #[test]
+    fn redo_replays_undone_moves() {
+        let mut game: Game<RawModeUi> = Game::new_game(3, 3, Default::default());
+        play_moves(&mut game, &[(0, 0), (0, 1), (2, 2), (1, 0)]);
+        game.undo().unwrap();
+        game.undo().unwrap();
+        assert_eq!(Stone::Empty, game.board().stone_at(2, 2));
+        assert_eq!(game.to_move(), Stone::Black);
+
+        game.redo().unwrap();
+        game.redo().unwrap();
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 0));
+        assert_eq!(Stone::White, game.board().stone_at(1, 0));
+        assert_eq!(game.prisoners().captured_by(Stone::White), 1);
+        assert_eq!(game.position.move_number(), 4);
+        assert_eq!(game.to_move(), Stone::Black);
+        let err = game.redo().unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MoveError>(),
+            Some(&MoveError::NothingToRedo)
+        );
+    }
+
+    #[test]
+    fn new_move_clears_redo() {
+        let mut game: Game<RawModeUi> = Game::new_game(3, 3, Default::default());
+        play_moves(&mut game, &[(0, 0), (0, 1)]);
+        game.undo().unwrap();
+        game.make_move(at(&game, 2, 2)).unwrap();
+        assert!(game.redo().is_err());
+        assert_eq!(
+            game.moves(),
+            &[
+                Play::Stone(GameMove::new(Stone::Black, at(&game, 0, 0), 1)),
+                Play::Stone(GameMove::new(Stone::White, at(&game, 2, 2), 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn undo_pass_returns_to_play() {
+        let mut game = setup_game("a1\npass\npass\nmark a1\nundo\nundo\nb2\nq\n");
+        game.start_game().unwrap();
+        assert_eq!(game.phase(), GamePhase::Playing);
+        assert!(game.dead_stones().is_empty());
+        assert_eq!(Stone::White, game.board().stone_at(1, 1));
+        assert_eq!(game.moves().len(), 2);
+    }
+
+    #[test]
+    fn undo_with_no_moves_is_reported() {
+        let mut game = setup_game("undo\nq\n");
+        game.start_game().unwrap();
+        let err = game.undo().unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MoveError>(),
+            Some(&MoveError::NothingToUndo)
+        );
+    }
+
+    #[test]
+    fn undo_keeps_ko_history() {
+        let mut game = setup_ko(KoRule::Simple);
+        // take back the capture and play it again, the ko is still forbidden
+        game.undo().unwrap();
+        game.make_move(at(&game, 1, 2)).unwrap();
+        let err = game.make_move(at(&game, 1, 1)).unwrap_err();
+        assert_eq!(err.downcast_ref::<MoveError>(), Some(&MoveError::Ko));
+    }
+
+    #[test]
+    fn sgf_records_setup_and_moves() {
+        let mut game: Game<RawModeUi> = Game::new_game(9, 9, Default::default())
+            .with_handicap(Handicap::Fixed(2))
+            .unwrap()
+            .with_player_names("Shusaku", "Gennan");
+        play_moves(&mut game, &[(2, 4), (6, 5)]);
+        game.pass();
+        game.resign();
+        assert_eq!(
+            game.to_sgf(),
+            format!(
+                "(;FF[4]GM[1]CA[UTF-8]AP[go:{}]SZ[9]KM[0.5]PB[Shusaku]PW[Gennan]HA[2]AB[cg][gc]RE[W+R]\n\
+                 ;W[ec];B[fg];W[])\n",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn sgf_rectangular_board() {
+        let mut game: Game<RawModeUi> = Game::new_game(5, 3, Default::default());
+        play_moves(&mut game, &[(2, 4)]);
+        let sgf = game.to_sgf();
+        assert!(sgf.contains("SZ[5:3]"));
+        assert!(sgf.contains(";B[ec])"));
+        assert!(!sgf.contains("RE["));
+    }
+
+    #[test]
+    fn moves_sgf_cannot_name_are_refused() {
+        let mut game: Game<RawModeUi> = Game::new_game(53, 53, Default::default());
+        let err = game.play(Stone::Black, Some(at(&game, 0, 52))).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MoveError>(),
+            Some(&MoveError::Unrecordable)
+        );
+        assert!(game.moves().is_empty());
+        assert_eq!(game.current_stone(), Stone::Black);
+        play_moves(&mut game, &[(51, 51)]);
+        game.undo().unwrap();
+        game.redo().unwrap();
+        assert_eq!(game.board().stone_at(51, 51), Stone::Black);
+    }
+
+    #[test]
+    fn sgf_round_trip() {
+        let mut game: Game<RawModeUi> = Game::new_game(9, 9, Default::default())
+            .with_handicap(Handicap::Fixed(3))
+            .unwrap()
+            .with_player_names("Black", "White")
+            .with_komi(0.5);
+        play_moves(&mut game, &[(2, 4), (6, 5), (0, 0)]);
+        game.pass();
+        let loaded: Game<RawModeUi> = Game::from_sgf(&game.to_sgf(), Default::default()).unwrap();
+        assert_eq!(loaded.board(), game.board());
+        assert_eq!(loaded.moves(), game.moves());
+        assert_eq!(loaded.handicap(), Some(Handicap::Fixed(3)));
+        assert_eq!(loaded.to_sgf(), game.to_sgf());
+    }
+
+    #[test]
+    fn sgf_moves_go_through_the_rules() {
+        let sgf = "(;SZ[3];B[ba];W[aa];B[ab];W[ca];B[bb];W[tt];B[bc])";
+        let game: Game<RawModeUi> = Game::from_sgf(sgf, Default::default()).unwrap();
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 0));
+        assert_eq!(game.prisoners().captured_by(Stone::Black), 1);
+        assert_eq!(
+            game.moves()[5],
+            Play::Pass {
+                stone: Stone::White,
+                move_number: 6
+            }
+        );
+
+        // Black retaking the ko at once is refused
+        let sgf = "(;SZ[4]AB[ba][ab][cb][bc]AW[ca][db][cc];W[bb];B[cb])";
+        let err = Game::<RawModeUi>::from_sgf(sgf, Default::default())
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.downcast_ref::<SgfError>(),
+            Some(&SgfError::Property {
+                node: 2,
+                ident: "B".to_string(),
+                reason: MoveError::Ko.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn sgf_setup_and_player_to_move() {
+        let sgf = "(;SZ[5]AB[aa:ab]AW[ee]PL[W])";
+        let game: Game<RawModeUi> = Game::from_sgf(sgf, Default::default()).unwrap();
+        assert_eq!(Stone::Black, game.board().stone_at(1, 0));
+        assert_eq!(Stone::White, game.board().stone_at(4, 4));
+        assert_eq!(game.current_stone(), Stone::White);
+        assert!(game.handicap().is_none());
+        assert!(game.moves().is_empty());
+    }
+
+    #[test]
+    fn sgf_free_handicap() {
+        let sgf = "(;SZ[9]HA[2]AB[aa][bb];W[ee])";
+        let game: Game<RawModeUi> = Game::from_sgf(sgf, Default::default()).unwrap();
+        assert_eq!(game.handicap(), Some(Handicap::Free(2)));
+        assert_eq!(game.handicap_stones(), &[(0, 0), (1, 1)]);
+        assert_eq!(Stone::White, game.board().stone_at(4, 4));
+    }
+
+    #[test]
+    fn bad_sgf_properties_are_reported() {
+        let property_error = |sgf: &str| {
+            let err = Game::<RawModeUi>::from_sgf(sgf, Default::default())
+                .err()
+                .unwrap();
+            match err.downcast_ref::<SgfError>() {
+                Some(SgfError::Property { node, ident, .. }) => (*node, ident.clone()),
+                other => panic!("unexpected error {other:?}"),
+            }
+        };
+        assert_eq!(property_error("(;SZ[nine])"), (0, "SZ".to_string()));
+        assert_eq!(property_error("(;KM[a lot])"), (0, "KM".to_string()));
+        assert_eq!(property_error("(;SZ[9];B[ee];W[ee])"), (2, "W".to_string()));
+        assert_eq!(property_error("(;SZ[9];B[jj])"), (1, "B".to_string()));
+        assert_eq!(
+            property_error("(;SZ[9]AB[ee];AE[ee])"),
+            (1, "AE".to_string())
+        );
+        // a move property can be built without a value, though not parsed
+        let mut game: Game<RawModeUi> = Game::new_game(9, 9, Default::default());
+        let mut node = sgf::Node::default();
+        node.push("B", vec![]);
+        game.tree.add_child(GameTree::ROOT, node);
+        let err = game.forward(0).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SgfError>(),
+            Some(SgfError::Property { .. })
         ));
-        assert!(game.stone_groups.connected(
-            game.board.index_of_pos((0, 1)),
-            game.board.index_of_pos((0, 2)),
+        let err = Game::<RawModeUi>::from_sgf("(;SZ[9];B[ee]", Default::default())
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<SgfError>(),
+            Some(SgfError::Syntax { .. })
         ));
     }
 
+    #[test]
+    fn moves_after_undo_start_a_variation() {
+        let mut game: Game<RawModeUi> = Game::new_game(3, 3, Default::default());
+        play_moves(&mut game, &[(0, 0), (0, 1)]);
+        game.undo().unwrap();
+        game.make_move(at(&game, 2, 2)).unwrap();
+        let first = game.tree().children(GameTree::ROOT)[0];
+        assert_eq!(game.tree().children(first).len(), 2);
+
+        game.previous_variation().unwrap();
+        assert_eq!(Stone::White, game.board().stone_at(0, 1));
+        assert_eq!(Stone::Empty, game.board().stone_at(2, 2));
+        game.next_variation().unwrap();
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 1));
+        assert_eq!(Stone::White, game.board().stone_at(2, 2));
+        let err = game.next_variation().unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MoveError>(),
+            Some(&MoveError::NoVariation)
+        );
+
+        // playing a move that is already a variation follows it
+        game.back().unwrap();
+        game.make_move(at(&game, 0, 1)).unwrap();
+        assert_eq!(game.tree().children(first).len(), 2);
+        assert_eq!(game.current_node(), game.tree().children(first)[0]);
+    }
+
+    #[test]
+    fn walking_the_tree_keeps_the_board_in_sync() {
+        let sgf = "(;SZ[3];B[aa];W[ba](;B[cc];W[ab])(;B[bb]))";
+        let mut game: Game<RawModeUi> = Game::from_sgf(sgf, Default::default()).unwrap();
+        // the main line is played out, capturing the corner stone
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 0));
+        assert_eq!(game.prisoners().captured_by(Stone::White), 1);
+        let capture = game.current_node();
+
+        game.back().unwrap();
+        game.back().unwrap();
+        assert_eq!(Stone::Black, game.board().stone_at(0, 0));
+        game.forward(1).unwrap();
+        assert_eq!(Stone::Black, game.board().stone_at(1, 1));
+        assert_eq!(game.prisoners().captured_by(Stone::White), 0);
+        assert_eq!(game.moves().len(), 3);
+
+        game.go_to(capture).unwrap();
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 0));
+        assert_eq!(Stone::Empty, game.board().stone_at(1, 1));
+        assert_eq!(game.moves().len(), 4);
+        game.go_to(GameTree::ROOT).unwrap();
+        assert!(game.moves().is_empty());
+        assert!(game.back().is_err());
+    }
+
+    #[test]
+    fn illegal_variations_are_refused_when_entered() {
+        let sgf = "(;SZ[3];B[aa](;W[bb])(;W[aa]))";
+        let mut game: Game<RawModeUi> = Game::from_sgf(sgf, Default::default()).unwrap();
+        let at = game.current_node();
+        assert!(game.next_variation().is_err());
+        assert_eq!(game.current_node(), at);
+        assert_eq!(Stone::White, game.board().stone_at(1, 1));
+    }
+
+    #[test]
+    fn sgf_round_trip_keeps_the_tree() {
+        let sgf = "(;FF[4]GM[1]CA[UTF-8]AP[go:0]SZ[9]KM[6.5]GN[review]\n\
+                   ;B[ee]C[center](;W[cc]TR[ee];B[gg]LB[cc:A][dd:B])(;W[gc]N[approach]SQ[ee]))\n";
+        let mut game: Game<RawModeUi> = Game::from_sgf(sgf, Default::default()).unwrap();
+        game.set_comment("comment");
+        game.add_markup(Markup::Triangle((2, 2)));
+        let saved = game.to_sgf();
+        let loaded: Game<RawModeUi> = Game::from_sgf(&saved, Default::default()).unwrap();
+        assert_eq!(loaded.to_sgf(), saved);
+        assert!(saved.contains("GN[review]"));
+        assert!(saved.contains(";B[gg]LB[cc:A][dd:B]C[comment]TR[cc])"));
+        assert!(saved.contains("(;W[gc]N[approach]SQ[ee])"));
+    }
+
+    #[test]
+    fn play_either_color() {
+        let mut game: Game<RawModeUi> = Game::new_game(3, 3, Default::default());
+        game.play(Stone::White, Some(at(&game, 1, 1))).unwrap();
+        game.play(Stone::White, Some(at(&game, 0, 0))).unwrap();
+        assert_eq!(game.to_move(), Stone::Black);
+        let refused = |game: &mut Game<RawModeUi>, point| {
+            let err = game.play(Stone::Black, Some(point)).unwrap_err();
+            err.downcast_ref::<MoveError>().cloned()
+        };
+        let occupied = at(&game, 1, 1);
+        assert_eq!(refused(&mut game, occupied), Some(MoveError::Occupied));
+        // a point of a larger board
+        let off_board = Board::new(4, 4).point(3, 0).unwrap();
+        assert_eq!(refused(&mut game, off_board), Some(MoveError::OutOfBounds));
+        game.play(Stone::Black, None).unwrap();
+        game.play(Stone::White, None).unwrap();
+        assert_eq!(game.phase(), GamePhase::Scoring);
+        game.play(Stone::Black, Some(at(&game, 2, 2))).unwrap();
+        assert_eq!(game.phase(), GamePhase::Playing);
+        assert_eq!(game.moves().len(), 5);
+
+        game.resign();
+        let free = at(&game, 2, 0);
+        assert_eq!(refused(&mut game, free), Some(MoveError::GameOver));
+    }
+
+    #[test]
+    fn refused_input_does_not_end_the_game() {
+        // a1 again is occupied, j1 is off the board and "x" is not a move
+        let mut game = setup_game("a1\na1\nj1\nx\nb2\nq\n");
+        game.start_game().unwrap();
+        assert_eq!(game.moves().len(), 2);
+        assert_eq!(Stone::White, game.board().stone_at(1, 1));
+    }
+
+    #[test]
+    fn komi_overrides_ruleset() {
+        let game = setup_game("").with_komi(0.5);
+        assert_eq!(game.komi(), 0.5);
+        let game = setup_game("").with_ruleset(Ruleset {
+            scoring: ScoringMethod::Area,
+            komi: 7.5,
+            ..Default::default()
+        });
+        assert_eq!(game.komi(), 7.5);
+        assert_eq!(game.score().white.total, 7.5);
+        assert_eq!(game.score().method, ScoringMethod::Area);
+    }
+
+    #[test]
+    fn resign_gives_opponent_the_win() {
+        let mut game = setup_game("a1\nresign\n");
+        game.start_game().unwrap();
+        assert_eq!(game.phase(), GamePhase::Finished);
+        assert_eq!(
+            game.result(),
+            Some(GameResult::Resignation {
+                winner: Stone::Black
+            })
+        );
+    }
+
+    #[test]
+    fn quit_leaves_no_result() {
+        let mut game = setup_game("a1\nq\n");
+        game.start_game().unwrap();
+        assert_eq!(game.phase(), GamePhase::Playing);
+        assert_eq!(game.result(), None);
+    }
+
+    #[test]
+    fn make_two_moves() {
+        let mut game = setup_game("a1\nb2\n");
+        game.update().unwrap();
+        game.update().unwrap();
+        assert_eq!(Stone::Black, game.board().stone_at(0, 0));
+        assert_eq!(Stone::White, game.board().stone_at(1, 1));
+    }
+
     #[test]
     fn dead_corner_stone() {
         let mut game: Game<RawModeUi> = Game::new_game(3, 3, Default::default());
-        game.make_move(0, 0).unwrap();
-        game.make_move(0, 1).unwrap();
-        game.make_move(2, 2).unwrap();
-        game.make_move(1, 0).unwrap();
+        game.make_move(at(&game, 0, 0)).unwrap();
+        game.make_move(at(&game, 0, 1)).unwrap();
+        game.make_move(at(&game, 2, 2)).unwrap();
+        game.make_move(at(&game, 1, 0)).unwrap();
         // dbg!(&board.chains.len());
-        // dbg!(&game.board);
-        print!("{}", &game.board);
-        print!("{:?}", &game.stone_groups);
-        // assert_eq!(Stone::Empty, game.board.stone_at(0, 0));
+        // dbg!(&game.board());
+        print!("{}", game.board());
+        // assert_eq!(Stone::Empty, game.board().stone_at(0, 0));
         let expected_board_state = vec![
             vec![Stone::Empty, Stone::White, Stone::Empty],
             vec![Stone::White, Stone::Empty, Stone::Empty],
             vec![Stone::Empty, Stone::Empty, Stone::Black],
         ];
-        assert_eq!(game.board.state, expected_board_state);
+        assert_eq!(game.board().state, expected_board_state);
+    }
+
+    /// Play the given moves in order, alternating colors starting with Black.
+    fn play_moves(game: &mut Game<RawModeUi>, moves: &[(usize, usize)]) {
+        for &(row, col) in moves {
+            game.make_move(at(game, row, col)).unwrap();
+        }
+    }
+
+    #[test]
+    fn dead_side_stones() {
+        let mut game: Game<RawModeUi> = Game::new_game(9, 9, Default::default());
+        play_moves(
+            &mut game,
+            &[
+                (0, 1),
+                (0, 0),
+                (0, 2),
+                (1, 1),
+                (8, 8),
+                (1, 2),
+                (8, 7),
+                (0, 3),
+            ],
+        );
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 1));
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 2));
+        assert_eq!(Stone::White, game.board().stone_at(0, 0));
+        assert_eq!(Stone::White, game.board().stone_at(1, 1));
+        assert_eq!(Stone::White, game.board().stone_at(1, 2));
+        assert_eq!(Stone::White, game.board().stone_at(0, 3));
+    }
+
+    #[test]
+    fn dead_center_stone() {
+        let mut game: Game<RawModeUi> = Game::new_game(9, 9, Default::default());
+        play_moves(
+            &mut game,
+            &[
+                (4, 4),
+                (3, 4),
+                (0, 0),
+                (5, 4),
+                (0, 8),
+                (4, 3),
+                (8, 0),
+                (4, 5),
+            ],
+        );
+        assert_eq!(Stone::Empty, game.board().stone_at(4, 4));
+        assert_eq!(Stone::White, game.board().stone_at(3, 4));
+        assert_eq!(Stone::White, game.board().stone_at(5, 4));
+        assert_eq!(Stone::White, game.board().stone_at(4, 3));
+        assert_eq!(Stone::White, game.board().stone_at(4, 5));
+    }
+
+    #[test]
+    fn suicide_is_rejected() {
+        let mut game: Game<RawModeUi> = Game::new_game(3, 3, Default::default());
+        play_moves(&mut game, &[(0, 1), (2, 2), (1, 0)]);
+        let err = game.make_move(at(&game, 0, 0)).unwrap_err();
+        assert_eq!(err.downcast_ref::<MoveError>(), Some(&MoveError::Suicide));
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 0));
+        // still White's turn after the refused move
+        assert_eq!(game.to_move(), Stone::White);
+        assert_eq!(game.position.move_number(), 3);
     }
 
     #[test]
-    fn correct_liberty_assignments() {
+    fn capturing_is_not_suicide() {
         let mut game: Game<RawModeUi> = Game::new_game(3, 3, Default::default());
-        game.make_move(0, 0).unwrap();
-        game.make_move(1, 1).unwrap();
-        game.make_move(2, 2).unwrap();
-
-        // beginning of array
-        let expected_libs = HashSet::from_iter(vec![1, 3]);
-        assert_eq!(game.stone_groups.liberties[0], expected_libs);
-
-        // middle of board
-        let expected_libs = HashSet::from_iter(vec![1, 3, 5, 7]);
-        assert_eq!(game.stone_groups.liberties[4], expected_libs);
-
-        // end of array
-        let expected_libs = HashSet::from_iter(vec![5, 7]);
-        assert_eq!(game.stone_groups.liberties[8], expected_libs);
-    }
-    //
-    // #[test]
-    // fn dead_side_stones() {
-    //     let mut board = Board::new(9, 9);
-    //     let black = Stone::Black;
-    //     let white = Stone::White;
-    //     board.update_board_state(&GameMove::new(black, (0, 1), 0));
-    //     board.update_board_state(&GameMove::new(black, (0, 2), 0));
-    //     board.update_board_state(&GameMove::new(white, (0, 0), 0));
-    //     board.update_board_state(&GameMove::new(white, (1, 1), 0));
-    //     board.update_board_state(&GameMove::new(white, (1, 2), 0));
-    //     board.update_board_state(&GameMove::new(white, (0, 3), 0));
-    //     assert_eq!(Stone::Empty, board.stone_at(0, 1));
-    //     assert_eq!(Stone::Empty, board.stone_at(0, 2));
-    //     assert_eq!(white, board.stone_at(0, 0));
-    //     assert_eq!(white, board.stone_at(1, 1));
-    //     assert_eq!(white, board.stone_at(1, 2));
-    //     assert_eq!(white, board.stone_at(0, 3));
-    // }
-    //
-    // #[test]
-    // fn dead_center_stone() {
-    //     let mut board = Board::new(9, 9);
-    //     let black = Stone::Black;
-    //     let white = Stone::White;
-    //     board.update_board_state(&GameMove::new(black, (4, 4), 0));
-    //     board.update_board_state(&GameMove::new(white, (3, 4), 0));
-    //     board.update_board_state(&GameMove::new(white, (5, 4), 0));
-    //     board.update_board_state(&GameMove::new(white, (4, 3), 0));
-    //     board.update_board_state(&GameMove::new(white, (4, 5), 0));
-    //     assert_eq!(Stone::Empty, board.stone_at(4, 4));
-    //     assert_eq!(white, board.stone_at(3, 4));
-    //     assert_eq!(white, board.stone_at(5, 4));
-    //     assert_eq!(white, board.stone_at(4, 3));
-    //     assert_eq!(white, board.stone_at(4, 5));
-    // }
+        // White at a1 has only b1 left, which Black fills with no liberties
+        // of its own except the one freed by the capture.
+        play_moves(&mut game, &[(0, 1), (0, 0), (2, 2), (1, 1), (0, 2), (2, 0)]);
+        game.make_move(at(&game, 1, 0)).unwrap();
+        assert_eq!(Stone::Black, game.board().stone_at(1, 0));
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 0));
+    }
+
+    #[test]
+    fn suicide_allowed_by_ruleset() {
+        let mut game: Game<RawModeUi> =
+            Game::new_game(3, 3, Default::default()).with_ruleset(Ruleset {
+                suicide_allowed: true,
+                ..Default::default()
+            });
+        play_moves(&mut game, &[(0, 1), (2, 2), (1, 0), (0, 0)]);
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 0));
+        assert_eq!(game.prisoners().captured_by(Stone::Black), 1);
+        assert_eq!(game.to_move(), Stone::Black);
+    }
+
+    /// Sets up a ko on a 4x4 board where Black has just captured at b2,
+    /// so that White retaking at b3 would repeat the previous position.
+    fn setup_ko(ko_rule: KoRule) -> Game<RawModeUi> {
+        let mut game: Game<RawModeUi> =
+            Game::new_game(4, 4, Default::default()).with_ruleset(Ruleset {
+                ko_rule,
+                ..Default::default()
+            });
+        play_moves(
+            &mut game,
+            &[
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 3),
+                (2, 1),
+                (2, 2),
+                (3, 3),
+                (1, 1),
+                (1, 2),
+            ],
+        );
+        assert_eq!(Stone::Empty, game.board().stone_at(1, 1));
+        game
+    }
+
+    #[test]
+    fn simple_ko_is_rejected() {
+        let mut game = setup_ko(KoRule::Simple);
+        let err = game.make_move(at(&game, 1, 1)).unwrap_err();
+        assert_eq!(err.downcast_ref::<MoveError>(), Some(&MoveError::Ko));
+        assert_eq!(Stone::Empty, game.board().stone_at(1, 1));
+        assert_eq!(Stone::Black, game.board().stone_at(1, 2));
+        assert_eq!(game.prisoners().captured_by(Stone::White), 0);
+        assert_eq!(game.position.move_number(), 9);
+        assert_eq!(game.to_move(), Stone::White);
+
+        // after a ko threat elsewhere the ko can be retaken
+        play_moves(&mut game, &[(3, 0), (2, 0)]);
+        game.make_move(at(&game, 1, 1)).unwrap();
+        assert_eq!(Stone::Empty, game.board().stone_at(1, 2));
+    }
+
+    #[test]
+    fn positional_superko_is_rejected() {
+        let mut game = setup_ko(KoRule::PositionalSuperko);
+        let err = game.make_move(at(&game, 1, 1)).unwrap_err();
+        assert_eq!(err.downcast_ref::<MoveError>(), Some(&MoveError::Superko));
+    }
+
+    #[test]
+    fn situational_superko_is_rejected() {
+        let mut game = setup_ko(KoRule::SituationalSuperko);
+        let err = game.make_move(at(&game, 1, 1)).unwrap_err();
+        assert_eq!(err.downcast_ref::<MoveError>(), Some(&MoveError::Superko));
+    }
+
+    #[test]
+    fn capture_three_stone_chain() {
+        let mut game: Game<RawModeUi> = Game::new_game(5, 5, Default::default());
+        // Black chain along the top edge, surrounded by White
+        play_moves(
+            &mut game,
+            &[
+                (0, 0),
+                (1, 0),
+                (0, 1),
+                (1, 1),
+                (0, 2),
+                (1, 2),
+                (4, 4),
+                (0, 3),
+            ],
+        );
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 0));
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 1));
+        assert_eq!(Stone::Empty, game.board().stone_at(0, 2));
+
+        assert_eq!(game.prisoners().captured_by(Stone::White), 3);
+        assert_eq!(game.prisoners().captured_by(Stone::Black), 0);
+        let mut captured = game.prisoners().captured_on_move(8).to_vec();
+        captured.sort();
+        assert_eq!(captured, vec![(0, 0), (0, 1), (0, 2)]);
+
+        // and the captured points can be played on again
+        game.make_move(at(&game, 0, 1)).unwrap();
+        assert_eq!(Stone::Black, game.board().stone_at(0, 1));
+    }
 }
@@ -1,4 +1,4 @@
-use crate::game_logic::stone::Stone;
+use crate::game_logic::{board::Point, stone::Stone};
 
 /// Represents a move made in the game.
 ///
@@ -9,7 +9,7 @@ use crate::game_logic::stone::Stone;
 /// # Fields
 ///
 /// * `stone`: The color of the stone played in the move.
-/// * `pos`: The position on the board where the stone was placed, represented as a tuple of `(row, col)`.
+/// * `pos`: The point on the board where the stone was placed.
 /// * `move_number`: The move number that this move corresponds to in the game's sequence of moves.
 ///
 /// The `GameMove` struct is used to track and represent individual moves within the game, allowing for recording,
@@ -18,15 +18,15 @@ use crate::game_logic::stone::Stone;
 ///
 /// Note that this struct does not enforce any game rules or validity of the move. It solely provides a convenient
 /// container to store and retrieve information about each move made during the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct GameMove {
     pub stone: Stone,
-    pub pos: (usize, usize),
+    pub pos: Point,
     pub move_number: usize,
 }
 
 impl GameMove {
-    // Should be y,x
-    pub fn new(stone: Stone, pos: (usize, usize), move_number: usize) -> Self {
+    pub fn new(stone: Stone, pos: Point, move_number: usize) -> Self {
         GameMove {
             stone,
             pos,
@@ -34,3 +34,30 @@ impl GameMove {
         }
     }
 }
+
+/// A turn taken by a player, as kept in the game's list of moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Play {
+    /// A stone placed on the board.
+    Stone(GameMove),
+    /// The player passed instead of placing a stone.
+    Pass { stone: Stone, move_number: usize },
+}
+
+impl Play {
+    /// The color of the player that took this turn.
+    pub fn stone(&self) -> Stone {
+        match self {
+            Play::Stone(mv) => mv.stone,
+            Play::Pass { stone, .. } => *stone,
+        }
+    }
+
+    /// The move number of this turn, counting passes.
+    pub fn move_number(&self) -> usize {
+        match self {
+            Play::Stone(mv) => mv.move_number,
+            Play::Pass { move_number, .. } => *move_number,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Handicap stones given to Black before the game starts.
+use anyhow::{bail, Result};
+
+/// How Black's handicap stones are put on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Handicap {
+    /// The stones go on the traditional star points. Only supported for
+    /// 2 to 9 stones on 9x9, 13x13 and 19x19 boards.
+    Fixed(usize),
+    /// Black places the stones anywhere, one after another, before White's
+    /// first move.
+    Free(usize),
+}
+
+impl Handicap {
+    /// The number of handicap stones Black gets.
+    pub fn stones(&self) -> usize {
+        match self {
+            Handicap::Fixed(n) | Handicap::Free(n) => *n,
+        }
+    }
+}
+
+/// The star points a fixed handicap of `stones` stones is placed on, as
+/// `(row, col)` with row 0 at the top of the board.
+///
+/// The points are picked in the usual order (the same one GTP's
+/// `fixed_handicap` uses), so the first two stones always take opposite
+/// corners.
+pub fn fixed_handicap_points(
+    width: usize,
+    height: usize,
+    stones: usize,
+) -> Result<Vec<(usize, usize)>> {
+    if width != height || ![9, 13, 19].contains(&width) {
+        bail!("Fixed handicap is only supported on 9x9, 13x13 and 19x19 boards");
+    }
+    if !(2..=9).contains(&stones) {
+        bail!("Fixed handicap must be between 2 and 9 stones");
+    }
+
+    // distance of the corner star points from the edge
+    let lo = if width == 9 { 2 } else { 3 };
+    let hi = width - 1 - lo;
+    let mid = width / 2;
+
+    let corners = [(hi, lo), (lo, hi), (lo, lo), (hi, hi)];
+    let sides = [(mid, lo), (mid, hi)];
+    let top_bottom = [(hi, mid), (lo, mid)];
+    let center = (mid, mid);
+
+    let mut points = Vec::with_capacity(stones);
+    points.extend_from_slice(&corners[..stones.min(4)]);
+    if stones >= 6 {
+        points.extend_from_slice(&sides);
+    }
+    if stones >= 8 {
+        points.extend_from_slice(&top_bottom);
+    }
+    if stones % 2 == 1 && stones >= 5 {
+        points.push(center);
+    }
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_stones_take_opposite_corners() {
+        let points = fixed_handicap_points(19, 19, 2).unwrap();
+        assert_eq!(points, vec![(15, 3), (3, 15)]);
+    }
+
+    #[test]
+    fn every_count_places_that_many_stones() {
+        for size in [9, 13, 19] {
+            for stones in 2..=9 {
+                let mut points = fixed_handicap_points(size, size, stones).unwrap();
+                assert_eq!(points.len(), stones);
+                points.sort();
+                points.dedup();
+                assert_eq!(points.len(), stones);
+            }
+        }
+    }
+
+    #[test]
+    fn odd_counts_use_the_center() {
+        let points = fixed_handicap_points(9, 9, 5).unwrap();
+        assert!(points.contains(&(4, 4)));
+        let points = fixed_handicap_points(13, 13, 7).unwrap();
+        assert!(points.contains(&(6, 6)));
+        assert!(points.contains(&(6, 3)));
+    }
+
+    #[test]
+    fn unsupported_handicaps_error() {
+        fixed_handicap_points(9, 9, 1).unwrap_err();
+        fixed_handicap_points(9, 9, 10).unwrap_err();
+        fixed_handicap_points(11, 11, 2).unwrap_err();
+        fixed_handicap_points(9, 13, 2).unwrap_err();
+    }
+}
@@ -0,0 +1,72 @@
+//! Keeps track of every position the board has been in, so that repeated
+//! positions (ko) can be refused.
+use crate::game_logic::{board::Board, error::MoveError, rules::KoRule, stone::Stone};
+
+/// A single position of the game: where the stones are, and who plays next.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Position {
+    state: Vec<Vec<Stone>>,
+    to_move: Stone,
+}
+
+/// Every position the game has been in, oldest first.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BoardHistory {
+    positions: Vec<Position>,
+}
+
+impl BoardHistory {
+    /// Start a history from the position the game begins in.
+    pub(crate) fn new(board: &Board, to_move: Stone) -> Self {
+        let mut history = Self::default();
+        history.push(board, to_move);
+        history
+    }
+
+    /// Remember the position reached after a move, with `to_move` playing next.
+    pub(crate) fn push(&mut self, board: &Board, to_move: Stone) {
+        self.positions.push(Position {
+            state: board.state.clone(),
+            to_move,
+        });
+    }
+
+    /// The number of positions remembered.
+    pub(crate) fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Forget every position after the first `len`, when the moves that
+    /// reached them are taken back.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.positions.truncate(len);
+    }
+
+    /// Checks whether reaching `board`, with `to_move` playing next, repeats an
+    /// earlier position in a way that `rule` forbids.
+    pub(crate) fn repetition(
+        &self,
+        board: &Board,
+        to_move: Stone,
+        rule: KoRule,
+    ) -> Option<MoveError> {
+        match rule {
+            KoRule::Simple => {
+                // only an immediate recapture, returning the board to how it
+                // was before the opponent's last move, is refused
+                let before_last = self.positions.len().checked_sub(2)?;
+                (self.positions[before_last].state == board.state).then_some(MoveError::Ko)
+            }
+            KoRule::PositionalSuperko => self
+                .positions
+                .iter()
+                .any(|pos| pos.state == board.state)
+                .then_some(MoveError::Superko),
+            KoRule::SituationalSuperko => self
+                .positions
+                .iter()
+                .any(|pos| pos.state == board.state && pos.to_move == to_move)
+                .then_some(MoveError::Superko),
+        }
+    }
+}
@@ -1,5 +1,17 @@
 pub(crate) mod board;
+pub(crate) mod error;
 pub mod game;
-mod game_move;
+pub(crate) mod game_move;
+pub(crate) mod handicap;
+mod history;
+pub(crate) mod notation;
+pub(crate) mod overlay;
+pub(crate) mod phase;
+mod players;
+pub(crate) mod position;
+pub(crate) mod prisoners;
+pub(crate) mod rules;
+pub mod scoring;
 pub(crate) mod stone;
+mod stone_removal;
 pub(crate) mod union_find;
@@ -0,0 +1,278 @@
+//! The names of the points of a board, as they are typed in and as the
+//! legends around a drawn board show them, and the GTP vertices that the
+//! gtp module reads and writes.
+use crate::game_logic::{
+    board::{Board, Point},
+    error::MoveError,
+};
+
+/// A way of naming the points of a board. Both name boards up to 25 points
+/// on a side.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Notation {
+    /// A row letter from `A` at the top, then a column number from 1 at the
+    /// left, like `c12`.
+    #[default]
+    RowLetter,
+    /// The style of GTP and OGS: a column letter from `A` at the left,
+    /// skipping `I`, then a row number from 1 at the bottom, like `D4`.
+    Gtp,
+}
+
+impl Notation {
+    /// The point of `board` that `input` names. Letters can be either case.
+    ///
+    /// ```rust
+    /// use go::{game::Game, Notation, RawModeUi};
+    /// let game: Game<RawModeUi> = Game::new_game(13, 13, Default::default());
+    /// let point = Notation::RowLetter.parse("c12", game.board()).unwrap();
+    /// assert_eq!((point.row(), point.col()), (2, 11));
+    /// let point = Notation::Gtp.parse("D4", game.board()).unwrap();
+    /// assert_eq!((point.row(), point.col()), (9, 3));
+    /// ```
+    pub fn parse(self, input: &str, board: &Board) -> Result<Point, MoveError> {
+        let input = input.trim();
+        let error = |reason: &str| MoveError::ParseError {
+            input: input.to_string(),
+            reason: reason.to_string(),
+        };
+        let mut chars = input.chars();
+        let letter = chars.next().map(|letter| letter.to_ascii_uppercase());
+        let digits = chars.as_str();
+        let (letter_name, number_name) = match self {
+            Notation::RowLetter => ("row", "Columns"),
+            Notation::Gtp => ("column", "Rows"),
+        };
+        let Some(letter) = letter.filter(char::is_ascii_alphabetic) else {
+            return Err(error(&format!("Non-alphabetical {letter_name}")));
+        };
+        if digits.is_empty() || digits.len() > 2 {
+            return Err(error(
+                "Move should be a letter and a number of up to 2 digits",
+            ));
+        }
+        let Some(number) = digits
+            .bytes()
+            .all(|digit| digit.is_ascii_digit())
+            .then(|| digits.parse::<usize>().ok())
+            .flatten()
+        else {
+            return Err(error(&format!("Non-digit {}", number_name.to_lowercase())));
+        };
+        if number == 0 {
+            return Err(error(&format!("{number_name} are numbered from 1")));
+        }
+
+        match self {
+            Notation::RowLetter => board.point((letter as u8 - b'A') as usize, number - 1),
+            Notation::Gtp if letter == 'I' => Err(error("There is no column I")),
+            // the rest is a GTP vertex
+            Notation::Gtp => {
+                let (row, col) = decode_vertex(input, board.width, board.height)
+                    .ok_or(MoveError::OutOfBounds)?;
+                board.point(row, col)
+            }
+        }
+    }
+
+    /// The name of `point` on a board `height` rows high, which `parse`
+    /// reads back.
+    pub fn format(self, point: Point, height: usize) -> String {
+        let row = self.row_label(point.row(), height);
+        let col = self.col_label(point.col());
+        match self {
+            Notation::RowLetter => format!("{row}{col}"),
+            Notation::Gtp => {
+                encode_vertex(point.into(), height).unwrap_or_else(|| format!("{col}{row}"))
+            }
+        }
+    }
+
+    /// The legend of row `row`, counted from the top of a board `height`
+    /// rows high.
+    pub(crate) fn row_label(self, row: usize, height: usize) -> String {
+        match self {
+            Notation::RowLetter => letter(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ", row),
+            Notation::Gtp => height.saturating_sub(row).to_string(),
+        }
+    }
+
+    /// The legend of column `col`, counted from the left.
+    pub(crate) fn col_label(self, col: usize) -> String {
+        match self {
+            Notation::RowLetter => (col + 1).to_string(),
+            Notation::Gtp => column_letter(col).unwrap_or('?').to_string(),
+        }
+    }
+}
+
+/// Letter number `index` of `letters`, or `?` past the last one.
+fn letter(letters: &[u8], index: usize) -> String {
+    letters
+        .get(index)
+        .map_or('?', |&letter| letter as char)
+        .to_string()
+}
+
+/// The column letters of GTP vertices. There is no `I`, to avoid confusing it with `J`.
+pub(crate) const GTP_COLUMNS: &[u8] = b"ABCDEFGHJKLMNOPQRSTUVWXYZ";
+
+/// The GTP vertex of `pos`, given as `(row, col)` with row 0 at the top of a
+/// board `height` rows high.
+pub fn encode_vertex(pos: (usize, usize), height: usize) -> Option<String> {
+    let (row, col) = pos;
+    let letter = column_letter(col)?;
+    Some(format!("{letter}{}", height.checked_sub(row)?))
+}
+
+/// The `(row, col)` of a GTP vertex on a board `width` by `height`, if it is
+/// on the board. Letters can be either case.
+pub fn decode_vertex(vertex: &str, width: usize, height: usize) -> Option<(usize, usize)> {
+    let mut chars = vertex.chars();
+    let letter = chars.next()?.to_ascii_uppercase();
+    let col = GTP_COLUMNS
+        .iter()
+        .position(|&column| column as char == letter)?;
+    let digits = chars.as_str();
+    if !digits.bytes().all(|digit| digit.is_ascii_digit()) {
+        return None;
+    }
+    let number: usize = digits.parse().ok()?;
+    let row = height.checked_sub(number)?;
+    (number >= 1 && col < width).then_some((row, col))
+}
+
+/// The letter GTP names column `col` with, counted from 0 at the left.
+pub(crate) fn column_letter(col: usize) -> Option<char> {
+    GTP_COLUMNS.get(col).map(|&letter| letter as char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> Board {
+        Board::new(19, 19)
+    }
+
+    fn parse(input: &str) -> Result<(usize, usize), MoveError> {
+        Notation::RowLetter
+            .parse(input, &board())
+            .map(<(usize, usize)>::from)
+    }
+
+    #[test]
+    fn parse_move_works_uppper_case() {
+        assert_eq!(parse("A1"), Ok((0, 0)));
+    }
+
+    #[test]
+    fn parse_move_works_lower_case() {
+        assert_eq!(parse("a1"), Ok((0, 0)));
+    }
+
+    #[test]
+    fn parse_move_two_digit_column() {
+        assert_eq!(parse("c12"), Ok((2, 11)));
+        assert_eq!(parse("s19"), Ok((18, 18)));
+        assert_eq!(parse("a20"), Err(MoveError::OutOfBounds));
+        assert_eq!(parse("t1"), Err(MoveError::OutOfBounds));
+    }
+
+    #[test]
+    fn parse_move_solo_alpha_should_error() {
+        parse("a").unwrap_err();
+    }
+
+    #[test]
+    fn parse_move_solo_digit_should_error() {
+        parse("1").unwrap_err();
+    }
+
+    #[test]
+    fn parse_move_empty_should_error() {
+        parse("").unwrap_err();
+    }
+
+    #[test]
+    fn parse_move_all_digits_should_error() {
+        parse("11").unwrap_err();
+    }
+
+    #[test]
+    fn parse_move_all_alpha_should_error() {
+        parse("aa").unwrap_err();
+    }
+
+    #[test]
+    fn parse_move_extra_char_should_error() {
+        parse("a1b").unwrap_err();
+        parse("a100").unwrap_err();
+        parse("a+1").unwrap_err();
+    }
+
+    #[test]
+    fn gtp_notation_counts_rows_from_the_bottom() {
+        let board = Board::new(9, 9);
+        let parse = |input| {
+            Notation::Gtp
+                .parse(input, &board)
+                .map(<(usize, usize)>::from)
+        };
+        assert_eq!(parse("A1"), Ok((8, 0)));
+        assert_eq!(parse("j9"), Ok((0, 8)));
+        assert_eq!(parse("K1"), Err(MoveError::OutOfBounds));
+        assert_eq!(parse("A10"), Err(MoveError::OutOfBounds));
+        assert_eq!(
+            parse("i5"),
+            Err(MoveError::ParseError {
+                input: "i5".to_string(),
+                reason: "There is no column I".to_string()
+            })
+        );
+        assert_eq!(
+            parse("a0").unwrap_err().to_string(),
+            "'a0': Rows are numbered from 1"
+        );
+    }
+
+    #[test]
+    fn formatting_reads_back() {
+        let board = board();
+        for notation in [Notation::RowLetter, Notation::Gtp] {
+            for index in 0..19 * 19 {
+                let point = board.point_at(index).unwrap();
+                let name = notation.format(point, board.height);
+                assert_eq!(notation.parse(&name, &board), Ok(point), "{name}");
+            }
+        }
+        let point = board.point(15, 3).unwrap();
+        assert_eq!(Notation::RowLetter.format(point, 19), "P4");
+        assert_eq!(Notation::Gtp.format(point, 19), "D4");
+    }
+
+    #[test]
+    fn vertices_skip_i() {
+        assert_eq!(encode_vertex((18, 0), 19).unwrap(), "A1");
+        assert_eq!(encode_vertex((0, 8), 19).unwrap(), "J19");
+        assert_eq!(encode_vertex((15, 3), 19).unwrap(), "D4");
+        assert!(encode_vertex((0, 25), 25).is_none());
+    }
+
+    #[test]
+    fn decoding_vertices() {
+        assert_eq!(decode_vertex("D4", 19, 19), Some((15, 3)));
+        assert_eq!(decode_vertex("j19", 19, 19), Some((0, 8)));
+        assert_eq!(decode_vertex("T1", 19, 19), Some((18, 18)));
+        for pos in [(0, 0), (4, 7), (8, 8)] {
+            let vertex = encode_vertex(pos, 9).unwrap();
+            assert_eq!(decode_vertex(&vertex, 9, 9), Some(pos));
+        }
+        assert_eq!(decode_vertex("I5", 19, 19), None);
+        assert_eq!(decode_vertex("K5", 9, 9), None);
+        assert_eq!(decode_vertex("A10", 9, 9), None);
+        assert_eq!(decode_vertex("A0", 9, 9), None);
+        assert_eq!(decode_vertex("pass", 9, 9), None);
+        assert_eq!(decode_vertex("A+5", 9, 9), None);
+    }
+}
@@ -0,0 +1,176 @@
+//! Marks drawn over the points of a board to help read it: the last move,
+//! the ko, and annotations such as territory, dead stones and SGF markup.
+use crate::game_logic::board::{Board, Point};
+use crate::game_logic::handicap::fixed_handicap_points;
+use crate::game_logic::stone::{Icon, Stone};
+use crate::sgf::Markup;
+use colored::{ColoredString, Colorize};
+use std::collections::HashMap;
+
+/// Something drawn on a point instead of its plain stone or `.`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mark {
+    /// The stone played last
+    LastMove,
+    /// The point the player to move may not retake a ko at
+    Ko,
+    /// An empty point counted for this color
+    Territory(Stone),
+    /// A stone of this color marked dead, which is off the board while the
+    /// game is scored
+    Dead(Stone),
+    Triangle,
+    Square,
+    /// A label, of which only the first character fits on the point
+    Label(char),
+}
+
+/// Marks for some points of a board, drawn over its stones. A point has at
+/// most one mark, the one put there last.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Overlay {
+    marks: HashMap<Point, Mark>,
+}
+
+impl Overlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw `mark` on `point`, in place of any mark already there.
+    pub fn mark(&mut self, point: Point, mark: Mark) {
+        self.marks.insert(point, mark);
+    }
+
+    /// The mark on `point`, if it has one.
+    pub fn get(&self, point: Point) -> Option<&Mark> {
+        self.marks.get(&point)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+
+    /// Draw the markup of an SGF node on `board`. Markup off the board is
+    /// never drawn.
+    pub fn add_markup(&mut self, markup: &[Markup], board: &Board) {
+        for markup in markup {
+            let (pos, mark) = match markup {
+                Markup::Triangle(pos) => (*pos, Mark::Triangle),
+                Markup::Square(pos) => (*pos, Mark::Square),
+                Markup::Label(pos, text) => match text.chars().next() {
+                    Some(first) => (*pos, Mark::Label(first)),
+                    None => continue,
+                },
+            };
+            if let Ok(point) = board.point(pos.0, pos.1) {
+                self.mark(point, mark);
+            }
+        }
+    }
+
+    /// Put the marks of `other` over these.
+    pub fn extend(&mut self, other: &Overlay) {
+        self.marks
+            .extend(other.marks.iter().map(|(&pos, mark)| (pos, mark.clone())));
+    }
+}
+
+/// The star points of a `width` by `height` board: the 9 points the fixed
+/// handicap stones go on, or 5 of them on 9x9. Other sizes have none.
+pub(crate) fn star_points(width: usize, height: usize) -> Vec<(usize, usize)> {
+    let stones = if width == 9 { 5 } else { 9 };
+    fixed_handicap_points(width, height, stones).unwrap_or_default()
+}
+
+/// The icon of a point holding `stone`, with `mark` drawn over it. An
+/// unmarked empty star point is a `+`. Every icon is one character wide, so
+/// marks do not move the rest of the board.
+pub(crate) fn point_icon(stone: Stone, mark: Option<&Mark>, star: bool) -> ColoredString {
+    let in_color = |color: Stone, icon: &str| match color {
+        Stone::Black => icon.blue(),
+        Stone::White => icon.green(),
+        Stone::Empty => icon.bright_white(),
+    };
+    match (mark, stone) {
+        (Some(Mark::LastMove), Stone::Black | Stone::White) => in_color(stone, "◉"),
+        (Some(Mark::Ko), Stone::Empty) => "□".red(),
+        (Some(Mark::Territory(owner)), Stone::Empty) => in_color(*owner, "▪"),
+        (Some(Mark::Dead(dead)), _) => in_color(*dead, "×"),
+        (Some(Mark::Triangle), _) => in_color(stone, "△"),
+        (Some(Mark::Square), _) => in_color(stone, "■"),
+        (Some(Mark::Label(label)), _) => in_color(stone, &label.to_string()),
+        (_, Stone::Empty) if star => "+".bright_white(),
+        _ => stone.get_icon(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_points_on_standard_sizes() {
+        assert_eq!(star_points(9, 9).len(), 5);
+        assert!(star_points(9, 9).contains(&(4, 4)));
+        assert_eq!(star_points(13, 13).len(), 9);
+        assert!(star_points(19, 19).contains(&(3, 15)));
+        assert!(star_points(7, 7).is_empty());
+        assert!(star_points(19, 13).is_empty());
+    }
+
+    #[test]
+    fn marks_replace_the_plain_icon() {
+        let plain = |stone: Stone| stone.get_icon().to_string();
+        let icon =
+            |stone, mark: Option<Mark>, star| point_icon(stone, mark.as_ref(), star).to_string();
+        assert_eq!(icon(Stone::Black, None, true), plain(Stone::Black));
+        assert_eq!(icon(Stone::Empty, None, false), plain(Stone::Empty));
+        assert_eq!(
+            icon(Stone::Empty, None, true),
+            "+".bright_white().to_string()
+        );
+        assert_eq!(
+            icon(Stone::White, Some(Mark::LastMove), false),
+            "◉".green().to_string()
+        );
+        assert_eq!(
+            icon(Stone::Empty, Some(Mark::Ko), true),
+            "□".red().to_string()
+        );
+        // marks that make no sense on the point are left out
+        assert_eq!(
+            icon(Stone::Black, Some(Mark::Ko), false),
+            plain(Stone::Black)
+        );
+        assert_eq!(
+            icon(Stone::Empty, Some(Mark::Dead(Stone::Black)), false),
+            "×".blue().to_string()
+        );
+        assert_eq!(
+            icon(Stone::Black, Some(Mark::Label('A')), false),
+            "A".blue().to_string()
+        );
+    }
+
+    #[test]
+    fn markup_becomes_marks() {
+        let board = Board::new(3, 3);
+        let point = |row, col| board.point(row, col).unwrap();
+        let mut overlay = Overlay::new();
+        overlay.add_markup(
+            &[
+                Markup::Triangle((0, 0)),
+                Markup::Label((1, 2), "Ab".to_string()),
+                Markup::Label((2, 2), String::new()),
+                Markup::Square((0, 0)),
+                Markup::Square((3, 3)),
+            ],
+            &board,
+        );
+        assert_eq!(overlay.get(point(0, 0)), Some(&Mark::Square));
+        assert_eq!(overlay.get(point(1, 2)), Some(&Mark::Label('A')));
+        assert_eq!(overlay.get(point(2, 2)), None);
+        assert_eq!(overlay.marks.len(), 2);
+    }
+}
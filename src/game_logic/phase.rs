@@ -0,0 +1,74 @@
+//! Where a game is in its life cycle, and how it ended.
+use crate::game_logic::stone::Stone;
+use std::fmt::Display;
+
+/// The stage a game is in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GamePhase {
+    /// Players are taking turns placing stones.
+    #[default]
+    Playing,
+    /// Both players passed in a row. Players mark the dead chains and
+    /// accept them before the game is scored.
+    Scoring,
+    /// The game has a result.
+    Finished,
+}
+
+/// How a finished game ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameResult {
+    /// The other player resigned.
+    Resignation { winner: Stone },
+    /// The game was counted and `winner` had `margin` more points.
+    Score { winner: Stone, margin: f32 },
+    /// The game was counted and both players had the same score (jigo).
+    Draw,
+}
+
+impl GameResult {
+    /// The player that won the game, or `None` for a draw.
+    pub fn winner(&self) -> Option<Stone> {
+        match self {
+            GameResult::Resignation { winner } | GameResult::Score { winner, .. } => Some(*winner),
+            GameResult::Draw => None,
+        }
+    }
+}
+
+impl Display for GameResult {
+    /// Formats the result the way SGF's `RE` property does, e.g. `B+R` or `W+6.5`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let color = |stone: &Stone| if *stone == Stone::Black { "B" } else { "W" };
+        match self {
+            GameResult::Resignation { winner } => write!(f, "{}+R", color(winner)),
+            GameResult::Score { winner, margin } => write!(f, "{}+{}", color(winner), margin),
+            GameResult::Draw => write!(f, "0"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resignation_display() {
+        let result = GameResult::Resignation {
+            winner: Stone::White,
+        };
+        assert_eq!(result.to_string(), "W+R");
+        assert_eq!(result.winner(), Some(Stone::White));
+    }
+
+    #[test]
+    fn score_display() {
+        let result = GameResult::Score {
+            winner: Stone::Black,
+            margin: 3.5,
+        };
+        assert_eq!(result.to_string(), "B+3.5");
+        assert_eq!(GameResult::Draw.to_string(), "0");
+        assert_eq!(GameResult::Draw.winner(), None);
+    }
+}
@@ -0,0 +1,94 @@
+//! The players and displays attached to a game next to its UI, so each
+//! color can be played from a different place.
+use crate::game_logic::{error::MoveError, scoring::Score, stone::Stone};
+use crate::ui::{GameEvent, GameView, Player, UserInterface};
+use anyhow::Result;
+use std::fmt;
+
+/// Who plays each color, when it is not the game's UI, and the other
+/// displays that show the game.
+#[derive(Default)]
+pub(crate) struct Players {
+    black: Option<Box<dyn Player>>,
+    white: Option<Box<dyn Player>>,
+    displays: Vec<Box<dyn UserInterface>>,
+}
+
+impl Players {
+    /// Let `player` choose the moves of `stone`, replacing any player it had.
+    pub(crate) fn seat(&mut self, stone: Stone, player: Box<dyn Player>) {
+        match stone {
+            Stone::Black => self.black = Some(player),
+            Stone::White => self.white = Some(player),
+            Stone::Empty => {}
+        }
+    }
+
+    /// Show the game on `display` as well.
+    pub(crate) fn add_display(&mut self, display: Box<dyn UserInterface>) {
+        self.displays.push(display);
+    }
+
+    /// The player choosing the moves of `stone`, if it has one.
+    pub(crate) fn player(&mut self, stone: Stone) -> Option<&mut (dyn Player + 'static)> {
+        match stone {
+            Stone::Black => self.black.as_deref_mut(),
+            Stone::White => self.white.as_deref_mut(),
+            Stone::Empty => None,
+        }
+    }
+
+    pub(crate) fn view(&mut self, game: &GameView) -> Result<()> {
+        for player in self.seated() {
+            player.see_game(game)?;
+        }
+        for display in &mut self.displays {
+            display.view(game)?;
+        }
+        Ok(())
+    }
+
+    /// Show the refused action to the player of `stone`, returning false if
+    /// that color is played from the game's UI instead.
+    pub(crate) fn show_error(&mut self, stone: Stone, err: &MoveError) -> Result<bool> {
+        match self.player(stone) {
+            Some(player) => player.action_refused(err).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    pub(crate) fn show_score(&mut self, score: &Score) -> Result<()> {
+        for player in self.seated() {
+            player.see_score(score)?;
+        }
+        for display in &mut self.displays {
+            display.show_score(score)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn notify(&mut self, event: &GameEvent) -> Result<()> {
+        for player in self.seated() {
+            player.hear(event)?;
+        }
+        for display in &mut self.displays {
+            display.notify(event)?;
+        }
+        Ok(())
+    }
+
+    /// Every player attached, Black's first.
+    fn seated(&mut self) -> impl Iterator<Item = &mut Box<dyn Player>> {
+        self.black.iter_mut().chain(self.white.iter_mut())
+    }
+}
+
+impl fmt::Debug for Players {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Players")
+            .field("black", &self.black.is_some())
+            .field("white", &self.white.is_some())
+            .field("displays", &self.displays.len())
+            .finish()
+    }
+}
@@ -0,0 +1,686 @@
+//! The rules of Go on their own, without a game around them or a UI to play
+//! them from. Bots, servers and analysis tools can play moves on a
+//! `Position` directly, and `Game` drives one from its UI.
+use anyhow::Result;
+
+use crate::game_logic::{
+    board::{Board, Point},
+    error::MoveError,
+    game_move::{GameMove, Play},
+    history::BoardHistory,
+    prisoners::Prisoners,
+    rules::{KoRule, Ruleset},
+    stone::Stone,
+};
+use crate::union_find::UnionFind;
+
+/// A position in a game of Go: the stones on the board, whose turn it is,
+/// the prisoners taken so far, and every earlier position, which together
+/// are all the rules need to judge the next move.
+///
+/// ```rust
+/// use go::{Position, Stone};
+/// let mut position = Position::new(9, 9);
+/// let tengen = position.board().point(4, 4).unwrap();
+/// position.play(Stone::Black, tengen).unwrap();
+/// assert_eq!(position.to_move(), Stone::White);
+/// assert!(!position.is_legal(Stone::White, tengen));
+/// assert_eq!(position.legal_moves().len(), 80);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Position {
+    stones: Stones,
+    prisoners: Prisoners,
+    ruleset: Ruleset,
+    history: BoardHistory,
+    to_move: Stone,
+    move_number: usize,
+    /// Number of passes played in a row
+    consecutive_passes: usize,
+}
+
+/// What playing a turn did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveOutcome {
+    /// The turn as played, numbered
+    pub play: Play,
+    /// The stones the move took off the board. These are the mover's own
+    /// stones for a suicide, when the ruleset allows it.
+    pub captured: Vec<(usize, usize)>,
+    /// Set when the ruleset says this pass ends play, and the game moves on
+    /// to scoring.
+    pub ends_play: bool,
+}
+
+/// The parts of a `Position` that a turn changes, used to take turns back.
+/// The history is only cut back to its length from before, so it is not
+/// copied with every turn.
+#[derive(Clone, Debug)]
+pub(crate) struct Checkpoint {
+    stones: Stones,
+    prisoners: Prisoners,
+    to_move: Stone,
+    move_number: usize,
+    consecutive_passes: usize,
+    history_len: usize,
+}
+
+impl Position {
+    /// An empty board `width` by `height` under the default ruleset, with
+    /// Black to play.
+    pub fn new(width: usize, height: usize) -> Self {
+        let board = Board::new(width, height);
+        let history = BoardHistory::new(&board, Stone::Black);
+        Position {
+            stones: Stones {
+                chains: UnionFind::new(width * height),
+                board,
+            },
+            prisoners: Prisoners::default(),
+            ruleset: Ruleset::default(),
+            history,
+            to_move: Stone::Black,
+            move_number: 0,
+            consecutive_passes: 0,
+        }
+    }
+
+    /// Judge moves by the given ruleset instead of the default one.
+    pub fn with_ruleset(mut self, ruleset: Ruleset) -> Self {
+        self.ruleset = ruleset;
+        self
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.stones.board
+    }
+
+    /// The stones captured by each player so far.
+    pub fn prisoners(&self) -> &Prisoners {
+        &self.prisoners
+    }
+
+    /// The number of prisoners `stone` has taken.
+    pub fn captures(&self, stone: Stone) -> usize {
+        self.prisoners.captured_by(stone)
+    }
+
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    /// The color whose turn it is.
+    pub fn to_move(&self) -> Stone {
+        self.to_move
+    }
+
+    /// Hand the turn to `stone`, like an SGF record's `PL` property does.
+    pub fn set_to_move(&mut self, stone: Stone) {
+        if stone != Stone::Empty {
+            self.to_move = stone;
+        }
+    }
+
+    /// The number of turns played so far, passes included.
+    pub fn move_number(&self) -> usize {
+        self.move_number
+    }
+
+    /// The number of passes played in a row, up to the last turn.
+    pub fn consecutive_passes(&self) -> usize {
+        self.consecutive_passes
+    }
+
+    /// Play a stone of color `stone` at `point`. Afterwards it is the
+    /// opponent's turn.
+    ///
+    /// Moves out of turn, points off the board or holding a stone, and moves
+    /// the ruleset forbids are refused with a `MoveError`, leaving the
+    /// position as it was. A point is off the board when it was made for a
+    /// larger one. To play out of turn, hand `stone` the turn with
+    /// `set_to_move` first.
+    pub fn play(&mut self, stone: Stone, point: Point) -> Result<MoveOutcome> {
+        let judged = self.judge(stone, point)?;
+        if let Some(violation) = self.repetition(stone, point, &judged) {
+            return Err(violation.into());
+        }
+        let opponent = stone.get_opponent().unwrap();
+        let suicide = judged.suicide;
+        let mut captured = self.stones.place(stone, point);
+        if suicide {
+            // the ruleset allows it, so the chain is taken off the board
+            captured = self.stones.capture_chain(self.stones.board.index_of(point));
+        }
+        self.move_number += 1;
+        let taker = if suicide { opponent } else { stone };
+        self.prisoners
+            .record(taker, self.move_number, captured.clone());
+        self.history.push(&self.stones.board, opponent);
+        self.consecutive_passes = 0;
+        self.to_move = opponent;
+        Ok(MoveOutcome {
+            play: Play::Stone(GameMove::new(stone, point, self.move_number)),
+            captured,
+            ends_play: false,
+        })
+    }
+
+    /// `stone` passes, and it is the opponent's turn. Under rulesets where
+    /// passing gives up a stone, the opponent gets a prisoner.
+    pub fn pass(&mut self, stone: Stone) -> Result<MoveOutcome> {
+        let Some(opponent) = stone.get_opponent() else {
+            return Err(MoveError::NoColor.into());
+        };
+        if stone != self.to_move {
+            return Err(MoveError::NotYourTurn.into());
+        }
+        self.prisoners
+            .add_captures(opponent, self.ruleset.pass_prisoners());
+        self.move_number += 1;
+        self.consecutive_passes += 1;
+        self.to_move = opponent;
+        self.history.push(&self.stones.board, opponent);
+        Ok(MoveOutcome {
+            play: Play::Pass {
+                stone,
+                move_number: self.move_number,
+            },
+            captured: Vec::new(),
+            ends_play: self.ruleset.ends_play(self.consecutive_passes, stone),
+        })
+    }
+
+    /// Whether `stone` could play at `point` now, without playing it. Moves
+    /// out of turn are not legal.
+    pub fn is_legal(&self, stone: Stone, point: Point) -> bool {
+        self.judge(stone, point)
+            .is_ok_and(|judged| self.repetition(stone, point, &judged).is_none())
+    }
+
+    /// Every point the player to move could play on, row by row from the top.
+    pub fn legal_moves(&self) -> Vec<Point> {
+        let board = self.board();
+        (0..board.width * board.height)
+            .filter_map(|index| board.point_at(index))
+            .filter(|&point| self.is_legal(self.to_move, point))
+            .collect()
+    }
+
+    /// The point the player to move may not retake a ko at, when the last
+    /// move took a single stone that the ko rule forbids taking back now.
+    pub fn ko_point(&self) -> Option<Point> {
+        let &[(row, col)] = self.prisoners.captured_on_move(self.move_number) else {
+            return None;
+        };
+        let point = self.board().point(row, col).ok()?;
+        let judged = self.judge(self.to_move, point).ok()?;
+        self.repetition(self.to_move, point, &judged).map(|_| point)
+    }
+
+    /// What putting `stone` at `point` would take off the board, worked out
+    /// from the liberties of the chains around it without placing it. Moves
+    /// the rules refuse before the history is looked at are errors.
+    fn judge(&self, stone: Stone, point: Point) -> Result<Judged> {
+        if stone == Stone::Empty {
+            return Err(MoveError::NoColor.into());
+        }
+        if stone != self.to_move {
+            return Err(MoveError::NotYourTurn.into());
+        }
+        if self.board().stone_on(point)? != Stone::Empty {
+            return Err(MoveError::Occupied.into());
+        }
+        let suicide = self.stones.is_suicide(point.into(), stone);
+        if suicide && !self.ruleset.suicide_allowed {
+            return Err(MoveError::Suicide.into());
+        }
+        Ok(Judged {
+            captured: self.stones.captures(point.into(), stone, suicide),
+            suicide,
+        })
+    }
+
+    /// The ko rule broken if `stone` at `point` repeats an earlier position.
+    /// The board is only copied to compare with the history when the move
+    /// could repeat one: under simple ko, only a move taking back a single
+    /// stone can.
+    fn repetition(&self, stone: Stone, point: Point, judged: &Judged) -> Option<MoveError> {
+        let rule = self.ruleset.ko_rule;
+        if rule == KoRule::Simple && judged.captured.len() != 1 {
+            return None;
+        }
+        let mut board = self.board().clone();
+        board.place_stone(&GameMove::new(stone, point, 0));
+        for &(row, col) in &judged.captured {
+            board.state[row][col] = Stone::Empty;
+        }
+        self.history.repetition(&board, stone.get_opponent()?, rule)
+    }
+
+    /// Put a stone on the board without playing it as a move, like handicap
+    /// stones and the stones an SGF record sets up. The point has to be empty.
+    pub(crate) fn add_stone(&mut self, stone: Stone, point: Point) {
+        self.stones.place(stone, point);
+    }
+
+    /// Start the history over from the position as it is now, for positions
+    /// that were set up rather than played.
+    pub(crate) fn restart_history(&mut self) {
+        self.history = BoardHistory::new(&self.stones.board, self.to_move);
+    }
+
+    /// Remember the position as it is now, after stones were set up in it.
+    pub(crate) fn remember(&mut self) {
+        self.history.push(&self.stones.board, self.to_move);
+    }
+
+    /// Every point of the chain the stone at `point` belongs to.
+    pub(crate) fn chain_at(&mut self, point: Point) -> Vec<(usize, usize)> {
+        let index = self.stones.board.index_of(point);
+        let width = self.stones.board.width;
+        self.stones
+            .chains
+            .chain_members(index)
+            .into_iter()
+            .map(|member| (member / width, member % width))
+            .collect()
+    }
+
+    pub(crate) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            stones: self.stones.clone(),
+            prisoners: self.prisoners.clone(),
+            to_move: self.to_move,
+            move_number: self.move_number,
+            consecutive_passes: self.consecutive_passes,
+            history_len: self.history.len(),
+        }
+    }
+
+    /// Put the position back to how it was at `checkpoint`.
+    pub(crate) fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.stones = checkpoint.stones;
+        self.prisoners = checkpoint.prisoners;
+        self.to_move = checkpoint.to_move;
+        self.move_number = checkpoint.move_number;
+        self.consecutive_passes = checkpoint.consecutive_passes;
+        self.history.truncate(checkpoint.history_len);
+    }
+}
+
+/// What a stone would do if it were put on the board.
+struct Judged {
+    /// The points of the stones it would take off
+    captured: Vec<(usize, usize)>,
+    /// Set when the stones taken off would be its own chain
+    suicide: bool,
+}
+
+/// The stones on the board, and the chains they make up.
+#[derive(Clone, Debug)]
+struct Stones {
+    board: Board,
+    chains: UnionFind,
+}
+
+impl Stones {
+    /// Put `stone` at `point` and take off any opposing chain left without
+    /// liberties, returning the points of the stones captured.
+    fn place(&mut self, stone: Stone, point: Point) -> Vec<(usize, usize)> {
+        self.board.place_stone(&GameMove::new(stone, point, 0));
+        self.create_libs(point.into());
+        self.update_board(point.into(), stone)
+    }
+
+    /// This function checks the local "neighbors" and dispatches events
+    /// based on different cases.
+    ///
+    /// Cases:
+    /// - If there is a neighboring stone of the same color, we need to
+    ///   combine the two stones since they are a connected "chain".
+    /// - If there is a neighboring stone of the opposing color, we need
+    ///   to remove the placed stone's position from the opposing stones'
+    ///   liberties list. Thereby needing to check for a capture.
+    ///
+    /// The stone must already be on the board when this is called.
+    /// Returns the points of every stone captured by the move.
+    fn update_board(&mut self, mv: (usize, usize), stone: Stone) -> Vec<(usize, usize)> {
+        let mut captured = Vec::new();
+        let move_index = self.board.flat_index(mv);
+        for (adj_row, adj_col) in self.board.neighbors(mv) {
+            let adjacent_index = self.board.flat_index((adj_row, adj_col));
+            let existing_stone = self.board.state[adj_row][adj_col];
+
+            if existing_stone == stone {
+                // union update stuff
+                self.chains.union(move_index, adjacent_index);
+            } else if existing_stone == stone.get_opponent().unwrap() {
+                // remove current move position from this adjacent stones representative chain
+                self.chains
+                    .remove_liberty_from_chain(adjacent_index, move_index);
+
+                if self.chains.no_liberties(adjacent_index) {
+                    captured.append(&mut self.capture_chain(adjacent_index));
+                }
+            }
+        }
+        captured
+    }
+
+    /// Remove every stone of the chain containing `index` from the board.
+    ///
+    /// The freed points are handed back as liberties to every chain that
+    /// touches them, and the captured indices are reset in the `UnionFind`
+    /// so that they can be played on again.
+    fn capture_chain(&mut self, index: usize) -> Vec<(usize, usize)> {
+        let members = self.chains.chain_members(index);
+        for &member in &members {
+            let (row, col) = (member / self.board.width, member % self.board.width);
+            self.board.state[row][col] = Stone::Empty;
+            self.chains.reset(member);
+        }
+
+        for &member in &members {
+            let pos = (member / self.board.width, member % self.board.width);
+            for (adj_row, adj_col) in self.board.neighbors(pos) {
+                if self.board.state[adj_row][adj_col] != Stone::Empty {
+                    let adjacent_index = self.board.flat_index((adj_row, adj_col));
+                    self.chains.add_liberty_to_chain(adjacent_index, member);
+                }
+            }
+        }
+
+        members
+            .into_iter()
+            .map(|member| (member / self.board.width, member % self.board.width))
+            .collect()
+    }
+
+    /// Checks whether playing `stone` at `pos` would leave its own chain
+    /// without any liberties while capturing nothing.
+    ///
+    /// This is worked out from the liberties the `UnionFind` already keeps
+    /// for the neighboring chains, without touching the board:
+    /// - an empty neighbor is a liberty for the new stone.
+    /// - a friendly chain with a liberty other than `pos` keeps the new stone alive.
+    /// - an opposing chain whose only liberty is `pos` gets captured, freeing a liberty.
+    fn is_suicide(&self, pos: (usize, usize), stone: Stone) -> bool {
+        let move_index = self.board.flat_index(pos);
+        for (adj_row, adj_col) in self.board.neighbors(pos) {
+            let adjacent_index = self.board.flat_index((adj_row, adj_col));
+            let existing_stone = self.board.state[adj_row][adj_col];
+            let libs = self.chains.chain_liberties(adjacent_index);
+            if existing_stone == Stone::Empty {
+                return false;
+            } else if existing_stone == stone {
+                if libs.iter().any(|&lib| lib != move_index) {
+                    return false;
+                }
+            } else if libs.len() == 1 && libs.contains(&move_index) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The points of the stones that `stone` at `pos` would take off: the
+    /// opposing chains whose last liberty is `pos`, or for a suicide the
+    /// chain the new stone would join, itself included.
+    fn captures(&self, pos: (usize, usize), stone: Stone, suicide: bool) -> Vec<(usize, usize)> {
+        let move_index = self.board.flat_index(pos);
+        let mut captured = if suicide {
+            vec![move_index]
+        } else {
+            Vec::new()
+        };
+        for (adj_row, adj_col) in self.board.neighbors(pos) {
+            let adjacent_index = self.board.flat_index((adj_row, adj_col));
+            let existing_stone = self.board.state[adj_row][adj_col];
+            let taken = if suicide {
+                existing_stone == stone
+            } else {
+                existing_stone == stone.get_opponent().unwrap()
+                    && self.chains.chain_liberties(adjacent_index).len() == 1
+            };
+            // neighbors in a chain already taken are not counted twice
+            if taken && !captured.contains(&adjacent_index) {
+                captured.extend(self.chains.chain_members(adjacent_index));
+            }
+        }
+        captured
+            .into_iter()
+            .map(|index| (index / self.board.width, index % self.board.width))
+            .collect()
+    }
+
+    /// Initialize the liberties of the stone being placed at `pos`.
+    ///
+    /// We can assume that the point is empty due to the callers checking for it
+    fn create_libs(&mut self, pos: (usize, usize)) {
+        let libs: Vec<usize> = self
+            .board
+            .neighbors(pos)
+            .into_iter()
+            .filter(|&(row, col)| self.board.state[row][col] == Stone::Empty)
+            .map(|adj| self.board.flat_index(adj))
+            .collect();
+        self.chains
+            .initialize_liberties_of_pos(self.board.flat_index(pos), libs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_logic::rules::KoRule;
+    use std::collections::HashSet;
+
+    /// The point at `(row, col)` of the position's board.
+    fn at(position: &Position, (row, col): (usize, usize)) -> Point {
+        position.board().point(row, col).unwrap()
+    }
+
+    /// Play the given moves in order, alternating colors starting with Black.
+    fn play_moves(position: &mut Position, moves: &[(usize, usize)]) {
+        for &pos in moves {
+            position
+                .play(position.to_move(), at(position, pos))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn make_two_connecting_moves() {
+        let mut position = Position::new(5, 5);
+        position.play(Stone::Black, at(&position, (0, 0))).unwrap();
+        position.set_to_move(Stone::Black);
+        position.play(Stone::Black, at(&position, (0, 1))).unwrap();
+        assert_eq!(Stone::Black, position.board().stone_at(0, 0));
+        assert_eq!(Stone::Black, position.board().stone_at(0, 1));
+        assert!(position.stones.chains.connected(0, 1));
+    }
+
+    #[test]
+    fn merge_two_chains_together() {
+        let mut position = Position::new(5, 5);
+        for col in [0, 2, 1] {
+            position.set_to_move(Stone::Black);
+            position
+                .play(Stone::Black, at(&position, (0, col)))
+                .unwrap();
+        }
+        let chains = &mut position.stones.chains;
+        assert!(chains.connected(0, 1));
+        assert!(chains.connected(0, 2));
+        assert!(chains.connected(1, 2));
+    }
+
+    #[test]
+    fn correct_liberty_assignments() {
+        let mut position = Position::new(3, 3);
+        play_moves(&mut position, &[(0, 0), (1, 1), (2, 2)]);
+
+        // beginning of array
+        let expected_libs = HashSet::from_iter(vec![1, 3]);
+        assert_eq!(position.stones.chains.liberties[0], expected_libs);
+
+        // middle of board
+        let expected_libs = HashSet::from_iter(vec![1, 3, 5, 7]);
+        assert_eq!(position.stones.chains.liberties[4], expected_libs);
+
+        // end of array
+        let expected_libs = HashSet::from_iter(vec![5, 7]);
+        assert_eq!(position.stones.chains.liberties[8], expected_libs);
+    }
+
+    #[test]
+    fn captures_free_liberties() {
+        let mut position = Position::new(5, 5);
+        // Black chain along the top edge, surrounded by White
+        play_moves(
+            &mut position,
+            &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2), (1, 2), (4, 4)],
+        );
+        let outcome = position.play(Stone::White, at(&position, (0, 3))).unwrap();
+        let mut captured = outcome.captured;
+        captured.sort();
+        assert_eq!(captured, vec![(0, 0), (0, 1), (0, 2)]);
+        assert_eq!(position.captures(Stone::White), 3);
+
+        // captured points are liberties of the surrounding chain again
+        let expected_libs = HashSet::from_iter(vec![0, 1, 2, 8, 10, 11, 12]);
+        assert_eq!(position.stones.chains.chain_liberties(5), &expected_libs);
+    }
+
+    #[test]
+    fn refused_moves_leave_the_position_alone() {
+        let mut position = Position::new(3, 3);
+        play_moves(&mut position, &[(0, 1), (2, 2), (1, 0)]);
+        assert!(!position.is_legal(Stone::White, at(&position, (0, 0))));
+        let refused = |position: &mut Position, stone, pos| {
+            let point = at(position, pos);
+            let err = position.play(stone, point).unwrap_err();
+            err.downcast_ref::<MoveError>().cloned()
+        };
+        assert_eq!(
+            refused(&mut position, Stone::White, (0, 0)),
+            Some(MoveError::Suicide)
+        );
+        assert_eq!(
+            refused(&mut position, Stone::White, (0, 1)),
+            Some(MoveError::Occupied)
+        );
+        let off_board = Position::new(4, 4).board().point(3, 0).unwrap();
+        let err = position.play(Stone::White, off_board).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MoveError>(),
+            Some(&MoveError::OutOfBounds)
+        );
+        assert_eq!(
+            refused(&mut position, Stone::Black, (1, 1)),
+            Some(MoveError::NotYourTurn)
+        );
+        assert!(!position.is_legal(Stone::Black, at(&position, (1, 1))));
+        assert_eq!(
+            refused(&mut position, Stone::Empty, (1, 1)),
+            Some(MoveError::NoColor)
+        );
+        assert_eq!(position.to_move(), Stone::White);
+        assert_eq!(position.move_number(), 3);
+        assert_eq!(Stone::Empty, position.board().stone_at(0, 0));
+    }
+
+    #[test]
+    fn ko_is_not_a_legal_move() {
+        let mut position = Position::new(4, 4).with_ruleset(Ruleset {
+            ko_rule: KoRule::Simple,
+            ..Default::default()
+        });
+        play_moves(
+            &mut position,
+            &[
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 3),
+                (2, 1),
+                (2, 2),
+                (3, 3),
+                (1, 1),
+                (1, 2),
+            ],
+        );
+        assert!(!position.is_legal(Stone::White, at(&position, (1, 1))));
+        assert!(!position.legal_moves().contains(&at(&position, (1, 1))));
+        assert_eq!(position.ko_point(), Some(at(&position, (1, 1))));
+        let err = position
+            .play(Stone::White, at(&position, (1, 1)))
+            .unwrap_err();
+        assert_eq!(err.downcast_ref::<MoveError>(), Some(&MoveError::Ko));
+        // the ko can be retaken once White has played elsewhere
+        position.pass(Stone::White).unwrap();
+        assert_eq!(position.ko_point(), None);
+    }
+
+    #[test]
+    fn judging_a_move_agrees_with_playing_it() {
+        for ko_rule in [KoRule::Simple, KoRule::PositionalSuperko] {
+            for suicide_allowed in [false, true] {
+                let mut position = Position::new(4, 4).with_ruleset(Ruleset {
+                    ko_rule,
+                    suicide_allowed,
+                    ..Default::default()
+                });
+                for turn in 0..80 {
+                    let stone = position.to_move();
+                    for index in 0..16 {
+                        let point = position.board().point_at(index).unwrap();
+                        let played = position.clone().play(stone, point);
+                        assert_eq!(position.is_legal(stone, point), played.is_ok());
+                        if let Ok(mut outcome) = played {
+                            let mut judged = position.judge(stone, point).unwrap().captured;
+                            judged.sort();
+                            outcome.captured.sort();
+                            assert_eq!(judged, outcome.captured);
+                        }
+                    }
+                    let legal = position.legal_moves();
+                    if legal.is_empty() || turn % 11 == 10 {
+                        position.pass(stone).unwrap();
+                    } else {
+                        position.play(stone, legal[turn * 7 % legal.len()]).unwrap();
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn passes_end_play() {
+        let mut position = Position::new(3, 3);
+        assert!(!position.pass(Stone::Black).unwrap().ends_play);
+        position.pass(Stone::Black).unwrap_err();
+        let outcome = position.pass(Stone::White).unwrap();
+        assert!(outcome.ends_play);
+        assert_eq!(
+            outcome.play,
+            Play::Pass {
+                stone: Stone::White,
+                move_number: 2
+            }
+        );
+        assert_eq!(position.consecutive_passes(), 2);
+        let err = position.pass(Stone::Empty).unwrap_err();
+        assert_eq!(err.downcast_ref::<MoveError>(), Some(&MoveError::NoColor));
+    }
+
+    #[test]
+    fn clones_are_independent() {
+        let mut position = Position::new(3, 3);
+        let before = position.clone();
+        position.play(Stone::Black, at(&position, (1, 1))).unwrap();
+        assert_eq!(before.legal_moves().len(), 9);
+        assert_eq!(position.legal_moves().len(), 8);
+        assert_eq!(before.to_move(), Stone::Black);
+    }
+}
@@ -0,0 +1,84 @@
+//! Book keeping for the stones each player has captured.
+use crate::game_logic::stone::Stone;
+use std::fmt::Display;
+
+/// Keeps count of the stones captured by each player, along with
+/// which points were captured on every move of the game.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Prisoners {
+    /// Number of White stones that Black has captured
+    black: usize,
+    /// Number of Black stones that White has captured
+    white: usize,
+    /// `captures[n]` holds the points captured by move number `n + 1`
+    captures: Vec<Vec<(usize, usize)>>,
+}
+
+impl Prisoners {
+    /// The number of stones captured *by* the given player.
+    pub fn captured_by(&self, stone: Stone) -> usize {
+        match stone {
+            Stone::Black => self.black,
+            Stone::White => self.white,
+            Stone::Empty => 0,
+        }
+    }
+
+    /// The points that were captured by the given move, 1-indexed like
+    /// `GameMove::move_number`. Moves that captured nothing return an empty slice.
+    pub fn captured_on_move(&self, move_number: usize) -> &[(usize, usize)] {
+        move_number
+            .checked_sub(1)
+            .and_then(|idx| self.captures.get(idx))
+            .map_or(&[], |points| points.as_slice())
+    }
+
+    /// Record the points captured by `stone` on `move_number`.
+    pub(crate) fn record(&mut self, stone: Stone, move_number: usize, points: Vec<(usize, usize)>) {
+        match stone {
+            Stone::Black => self.black += points.len(),
+            Stone::White => self.white += points.len(),
+            Stone::Empty => {}
+        }
+        if self.captures.len() < move_number {
+            self.captures.resize(move_number, Vec::new());
+        }
+        self.captures[move_number - 1] = points;
+    }
+
+    /// Give `stone` extra prisoners that were not captured by a move,
+    /// such as dead stones removed at the end of the game.
+    pub(crate) fn add_captures(&mut self, stone: Stone, count: usize) {
+        match stone {
+            Stone::Black => self.black += count,
+            Stone::White => self.white += count,
+            Stone::Empty => {}
+        }
+    }
+}
+
+impl Display for Prisoners {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Captures - Black: {}  White: {}", self.black, self.white)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_counts_per_player() {
+        let mut prisoners = Prisoners::default();
+        prisoners.record(Stone::Black, 1, vec![]);
+        prisoners.record(Stone::White, 2, vec![(0, 0), (0, 1)]);
+        prisoners.record(Stone::Black, 3, vec![(4, 4)]);
+        assert_eq!(prisoners.captured_by(Stone::Black), 1);
+        assert_eq!(prisoners.captured_by(Stone::White), 2);
+        assert_eq!(prisoners.captured_on_move(1), &[]);
+        assert_eq!(prisoners.captured_on_move(2), &[(0, 0), (0, 1)]);
+        assert_eq!(prisoners.captured_on_move(3), &[(4, 4)]);
+        assert_eq!(prisoners.captured_on_move(0), &[]);
+        assert_eq!(prisoners.captured_on_move(9), &[]);
+    }
+}
@@ -0,0 +1,209 @@
+//! The rules that vary between the different rulesets Go is played under.
+use crate::game_logic::{scoring::ScoringMethod, stone::Stone};
+
+/// Which repeated positions a move is not allowed to create.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KoRule {
+    /// A stone may not immediately recapture a single stone in a ko, i.e.
+    /// recreate the position from before the opponent's last move.
+    #[default]
+    Simple,
+    /// No move may recreate any earlier board position.
+    PositionalSuperko,
+    /// No move may recreate an earlier board position with the same player to move.
+    SituationalSuperko,
+}
+
+/// Extra points White receives for Black's handicap stones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HandicapCompensation {
+    /// Handicap stones are not compensated.
+    #[default]
+    None,
+    /// One point for every handicap stone (e.g. Chinese, Ing).
+    PerStone,
+    /// One point for every handicap stone after the first (AGA).
+    PerStoneAfterFirst,
+}
+
+/// Describes the rules a `Game` is played with.
+///
+/// Use one of the named rulesets, or adjust one of them with struct update
+/// syntax. The default is `Ruleset::japanese()`.
+///
+/// ```rust
+/// use go::{KoRule, Ruleset};
+/// let ruleset = Ruleset {
+///     ko_rule: KoRule::PositionalSuperko,
+///     ..Ruleset::japanese()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ruleset {
+    /// Whether a move that leaves its own chain without liberties may be
+    /// played, removing that chain from the board (e.g. New Zealand, Ing).
+    pub suicide_allowed: bool,
+    /// How repeated positions are prevented.
+    pub ko_rule: KoRule,
+    /// How the points are counted once the game is over.
+    pub scoring: ScoringMethod,
+    /// Points given to White, unless the game sets its own komi.
+    pub komi: f32,
+    /// Komi used instead of `komi` in handicap games.
+    pub handicap_komi: f32,
+    /// Extra points White receives in handicap games.
+    pub handicap_compensation: HandicapCompensation,
+    /// AGA pass stones: every pass hands the opponent a prisoner, and play
+    /// only ends on two passes in a row when White passes last.
+    pub pass_stones: bool,
+}
+
+impl Ruleset {
+    /// Japanese rules: territory scoring, simple ko, no suicide.
+    pub fn japanese() -> Self {
+        Self {
+            suicide_allowed: false,
+            ko_rule: KoRule::Simple,
+            scoring: ScoringMethod::Territory,
+            komi: 6.5,
+            handicap_komi: 0.5,
+            handicap_compensation: HandicapCompensation::None,
+            pass_stones: false,
+        }
+    }
+
+    /// Chinese rules: area scoring, positional superko, no suicide, and
+    /// White is compensated for every handicap stone.
+    pub fn chinese() -> Self {
+        Self {
+            suicide_allowed: false,
+            ko_rule: KoRule::PositionalSuperko,
+            scoring: ScoringMethod::Area,
+            komi: 7.5,
+            handicap_komi: 0.5,
+            handicap_compensation: HandicapCompensation::PerStone,
+            pass_stones: false,
+        }
+    }
+
+    /// American Go Association rules: area scoring with pass stones,
+    /// situational superko, no suicide.
+    pub fn aga() -> Self {
+        Self {
+            suicide_allowed: false,
+            ko_rule: KoRule::SituationalSuperko,
+            scoring: ScoringMethod::Area,
+            komi: 7.5,
+            handicap_komi: 0.5,
+            handicap_compensation: HandicapCompensation::PerStoneAfterFirst,
+            pass_stones: true,
+        }
+    }
+
+    /// New Zealand rules: area scoring, situational superko, suicide allowed.
+    pub fn new_zealand() -> Self {
+        Self {
+            suicide_allowed: true,
+            ko_rule: KoRule::SituationalSuperko,
+            scoring: ScoringMethod::Area,
+            komi: 7.0,
+            handicap_komi: 0.0,
+            handicap_compensation: HandicapCompensation::None,
+            pass_stones: false,
+        }
+    }
+
+    /// Tromp–Taylor rules: area scoring, positional superko, suicide allowed.
+    pub fn tromp_taylor() -> Self {
+        Self {
+            suicide_allowed: true,
+            ko_rule: KoRule::PositionalSuperko,
+            scoring: ScoringMethod::Area,
+            komi: 7.5,
+            handicap_komi: 0.5,
+            handicap_compensation: HandicapCompensation::None,
+            pass_stones: false,
+        }
+    }
+
+    /// Ing rules: area scoring, suicide allowed, 8 points komi. Ing's own
+    /// ko rules are approximated with situational superko.
+    pub fn ing() -> Self {
+        Self {
+            suicide_allowed: true,
+            ko_rule: KoRule::SituationalSuperko,
+            scoring: ScoringMethod::Area,
+            komi: 8.0,
+            handicap_komi: 0.5,
+            handicap_compensation: HandicapCompensation::PerStone,
+            pass_stones: false,
+        }
+    }
+
+    /// The komi for a game with `handicap_stones` handicap stones, when the
+    /// game does not set its own.
+    pub fn default_komi(&self, handicap_stones: usize) -> f32 {
+        if handicap_stones > 0 {
+            self.handicap_komi
+        } else {
+            self.komi
+        }
+    }
+
+    /// The extra points White gets for Black's `handicap_stones`.
+    pub fn compensation(&self, handicap_stones: usize) -> f32 {
+        let points = match self.handicap_compensation {
+            HandicapCompensation::None => 0,
+            HandicapCompensation::PerStone => handicap_stones,
+            HandicapCompensation::PerStoneAfterFirst => handicap_stones.saturating_sub(1),
+        };
+        points as f32
+    }
+
+    /// The prisoners handed to the opponent when a player passes.
+    pub fn pass_prisoners(&self) -> usize {
+        usize::from(self.pass_stones)
+    }
+
+    /// Whether `consecutive_passes` passes in a row, the last of them
+    /// played by `last_to_pass`, end play.
+    pub fn ends_play(&self, consecutive_passes: usize, last_to_pass: Stone) -> bool {
+        consecutive_passes >= 2 && (!self.pass_stones || last_to_pass == Stone::White)
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::japanese()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compensation_per_ruleset() {
+        assert_eq!(Ruleset::japanese().compensation(4), 0.0);
+        assert_eq!(Ruleset::chinese().compensation(4), 4.0);
+        assert_eq!(Ruleset::aga().compensation(4), 3.0);
+        assert_eq!(Ruleset::aga().compensation(0), 0.0);
+    }
+
+    #[test]
+    fn default_komi_depends_on_handicap() {
+        assert_eq!(Ruleset::chinese().default_komi(0), 7.5);
+        assert_eq!(Ruleset::chinese().default_komi(2), 0.5);
+        assert_eq!(Ruleset::new_zealand().default_komi(3), 0.0);
+    }
+
+    #[test]
+    fn aga_play_ends_on_white_pass() {
+        let aga = Ruleset::aga();
+        assert!(!aga.ends_play(2, Stone::Black));
+        assert!(aga.ends_play(2, Stone::White));
+        assert!(aga.ends_play(3, Stone::White));
+        assert!(Ruleset::japanese().ends_play(2, Stone::Black));
+        assert!(!Ruleset::japanese().ends_play(1, Stone::White));
+    }
+}
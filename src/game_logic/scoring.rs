@@ -0,0 +1,430 @@
+//! Counting the score of a finished game.
+//!
+//! The scoring functions only look at a `Board` and the prisoners taken
+//! during the game. Dead stones are expected to be removed from the board
+//! (and added to the prisoners) before scoring.
+use crate::game_logic::{board::Board, phase::GameResult, prisoners::Prisoners, stone::Stone};
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Display,
+};
+
+/// How points are counted at the end of the game.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScoringMethod {
+    /// Japanese style: surrounded empty points plus captured stones.
+    #[default]
+    Territory,
+    /// Chinese style: stones on the board plus surrounded empty points.
+    Area,
+}
+
+/// The points one player scored, broken down by where they came from.
+///
+/// Every field is filled in regardless of the scoring method, but only the
+/// ones the method counts make up `total`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlayerScore {
+    /// Stones of this color on the board
+    pub stones: usize,
+    /// Empty points surrounded only by this color
+    pub territory: usize,
+    /// Opposing stones this player captured
+    pub captures: usize,
+    /// Compensation points, only ever given to White
+    pub komi: f32,
+    pub total: f32,
+}
+
+/// The score of both players at the end of a game.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Score {
+    pub method: ScoringMethod,
+    pub black: PlayerScore,
+    pub white: PlayerScore,
+}
+
+impl Score {
+    /// The result of the game going by this score.
+    pub fn result(&self) -> GameResult {
+        let margin = self.black.total - self.white.total;
+        if margin > 0.0 {
+            GameResult::Score {
+                winner: Stone::Black,
+                margin,
+            }
+        } else if margin < 0.0 {
+            GameResult::Score {
+                winner: Stone::White,
+                margin: -margin,
+            }
+        } else {
+            GameResult::Draw
+        }
+    }
+}
+
+impl Display for Score {
+    /// A small table with the breakdown of both players' points.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:<10}{:>8}{:>8}", "", "Black", "White")?;
+        writeln!(
+            f,
+            "{:<10}{:>8}{:>8}",
+            "Stones", self.black.stones, self.white.stones
+        )?;
+        writeln!(
+            f,
+            "{:<10}{:>8}{:>8}",
+            "Territory", self.black.territory, self.white.territory
+        )?;
+        writeln!(
+            f,
+            "{:<10}{:>8}{:>8}",
+            "Captures", self.black.captures, self.white.captures
+        )?;
+        writeln!(
+            f,
+            "{:<10}{:>8}{:>8}",
+            "Komi", self.black.komi, self.white.komi
+        )?;
+        writeln!(
+            f,
+            "{:<10}{:>8}{:>8}",
+            "Total", self.black.total, self.white.total
+        )?;
+        write!(f, "Result: {}", self.result())
+    }
+}
+
+/// Who an empty point belongs to when the game is counted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Territory {
+    Black,
+    White,
+    /// Belongs to nobody: dame, or a point that cannot be counted because
+    /// of seki.
+    Neutral,
+}
+
+impl Territory {
+    /// The territory a region surrounded only by `stone` would be.
+    fn of(stone: Stone) -> Self {
+        match stone {
+            Stone::Black => Territory::Black,
+            Stone::White => Territory::White,
+            Stone::Empty => Territory::Neutral,
+        }
+    }
+}
+
+/// Score `board` with the given method, giving White `komi` points.
+pub fn score(board: &Board, prisoners: &Prisoners, komi: f32, method: ScoringMethod) -> Score {
+    let territory = classify_territory(board, method);
+    let mut black = player_score(board, &territory, prisoners, Stone::Black);
+    let mut white = player_score(board, &territory, prisoners, Stone::White);
+    white.komi = komi;
+    for player in [&mut black, &mut white] {
+        let counted = match method {
+            ScoringMethod::Area => player.stones + player.territory,
+            ScoringMethod::Territory => player.territory + player.captures,
+        };
+        player.total = counted as f32 + player.komi;
+    }
+    Score {
+        method,
+        black,
+        white,
+    }
+}
+
+fn player_score(
+    board: &Board,
+    territory: &[Vec<Option<Territory>>],
+    prisoners: &Prisoners,
+    stone: Stone,
+) -> PlayerScore {
+    PlayerScore {
+        stones: board
+            .state
+            .iter()
+            .flatten()
+            .filter(|&&s| s == stone)
+            .count(),
+        territory: territory
+            .iter()
+            .flatten()
+            .filter(|&&t| t == Some(Territory::of(stone)))
+            .count(),
+        captures: prisoners.captured_by(stone),
+        ..Default::default()
+    }
+}
+
+/// Works out who every empty point of `board` belongs to, with `None` for
+/// the points that hold a stone.
+///
+/// Every empty region is flood filled. A region bordered by only one color
+/// belongs to that color; any other region is neutral (dame). Under
+/// territory scoring the eyes of chains in seki are neutral as well, since
+/// neither player can claim them. Area scoring still counts them.
+pub fn classify_territory(board: &Board, method: ScoringMethod) -> Vec<Vec<Option<Territory>>> {
+    let chains = Chains::new(board);
+    let in_seki = match method {
+        ScoringMethod::Territory => chains.in_seki(board),
+        ScoringMethod::Area => HashSet::new(),
+    };
+
+    let mut owner: Vec<Vec<Option<Territory>>> = board
+        .state
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&s| (s == Stone::Empty).then_some(Territory::Neutral))
+                .collect()
+        })
+        .collect();
+    let mut visited = vec![vec![false; board.width]; board.height];
+    for row in 0..board.height {
+        for col in 0..board.width {
+            if visited[row][col] || board.state[row][col] != Stone::Empty {
+                continue;
+            }
+            let mut region = Vec::new();
+            let mut borders = (false, false); // (black, white)
+            let mut touches_seki = false;
+            let mut queue = VecDeque::from([(row, col)]);
+            visited[row][col] = true;
+            while let Some(pos) = queue.pop_front() {
+                region.push(pos);
+                for (r, c) in board.neighbors(pos) {
+                    match board.state[r][c] {
+                        Stone::Black => borders.0 = true,
+                        Stone::White => borders.1 = true,
+                        Stone::Empty if !visited[r][c] => {
+                            visited[r][c] = true;
+                            queue.push_back((r, c));
+                        }
+                        Stone::Empty => {}
+                    }
+                    if board.state[r][c] != Stone::Empty {
+                        touches_seki |= in_seki.contains(&chains.id[r][c]);
+                    }
+                }
+            }
+            let region_owner = match borders {
+                _ if touches_seki => Territory::Neutral,
+                (true, false) => Territory::Black,
+                (false, true) => Territory::White,
+                _ => Territory::Neutral,
+            };
+            for (r, c) in region {
+                owner[r][c] = Some(region_owner);
+            }
+        }
+    }
+    owner
+}
+
+/// The chains of stones on a board, found by flood filling stones of the
+/// same color. Scoring only has a `Board` to work with, so it cannot use
+/// the `UnionFind` the game keeps.
+struct Chains {
+    /// The chain each point belongs to, meaningless for empty points
+    id: Vec<Vec<usize>>,
+    /// The color and liberties of each chain
+    chains: Vec<(Stone, HashSet<(usize, usize)>)>,
+}
+
+impl Chains {
+    fn new(board: &Board) -> Self {
+        let mut id = vec![vec![usize::MAX; board.width]; board.height];
+        let mut chains = Vec::new();
+        for row in 0..board.height {
+            for col in 0..board.width {
+                let stone = board.state[row][col];
+                if stone == Stone::Empty || id[row][col] != usize::MAX {
+                    continue;
+                }
+                let mut liberties = HashSet::new();
+                let mut queue = VecDeque::from([(row, col)]);
+                id[row][col] = chains.len();
+                while let Some(pos) = queue.pop_front() {
+                    for (r, c) in board.neighbors(pos) {
+                        if board.state[r][c] == Stone::Empty {
+                            liberties.insert((r, c));
+                        } else if board.state[r][c] == stone && id[r][c] == usize::MAX {
+                            id[r][c] = chains.len();
+                            queue.push_back((r, c));
+                        }
+                    }
+                }
+                chains.push((stone, liberties));
+            }
+        }
+        Chains { id, chains }
+    }
+
+    /// The chains in seki: those sharing a liberty with an opposing chain
+    /// that neither player can fill without putting themselves in atari.
+    fn in_seki(&self, board: &Board) -> HashSet<usize> {
+        let mut in_seki = HashSet::new();
+        for row in 0..board.height {
+            for col in 0..board.width {
+                if board.state[row][col] != Stone::Empty {
+                    continue;
+                }
+                let adjacent: HashSet<usize> = board
+                    .neighbors((row, col))
+                    .into_iter()
+                    .filter(|&(r, c)| board.state[r][c] != Stone::Empty)
+                    .map(|(r, c)| self.id[r][c])
+                    .collect();
+                let shared = [Stone::Black, Stone::White]
+                    .iter()
+                    .all(|&stone| adjacent.iter().any(|&chain| self.chains[chain].0 == stone));
+                if shared
+                    && self.fills_into_atari(board, (row, col), Stone::Black, &adjacent)
+                    && self.fills_into_atari(board, (row, col), Stone::White, &adjacent)
+                {
+                    in_seki.extend(adjacent);
+                }
+            }
+        }
+        in_seki
+    }
+
+    /// Whether `stone` playing on the empty point `pos` would leave its own
+    /// chain with at most one liberty, without capturing anything.
+    fn fills_into_atari(
+        &self,
+        board: &Board,
+        pos: (usize, usize),
+        stone: Stone,
+        adjacent: &HashSet<usize>,
+    ) -> bool {
+        let mut liberties: HashSet<(usize, usize)> = board
+            .neighbors(pos)
+            .into_iter()
+            .filter(|&(r, c)| board.state[r][c] == Stone::Empty)
+            .collect();
+        for &chain in adjacent {
+            let (color, chain_libs) = &self.chains[chain];
+            if *color == stone {
+                liberties.extend(chain_libs);
+            } else if chain_libs.len() == 1 {
+                // filling the last liberty captures, so it is not seki
+                return false;
+            }
+        }
+        liberties.remove(&pos);
+        liberties.len() <= 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a board from rows of `X` (Black), `O` (White) and `.` (empty).
+    fn board_from(rows: &[&str]) -> Board {
+        let mut board = Board::new(rows[0].len(), rows.len());
+        for (r, row) in rows.iter().enumerate() {
+            for (c, ch) in row.chars().enumerate() {
+                board.state[r][c] = match ch {
+                    'X' => Stone::Black,
+                    'O' => Stone::White,
+                    _ => Stone::Empty,
+                };
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn area_scoring_counts_stones_and_territory() {
+        let board = board_from(&["..XO.", "..XO.", "..XO.", "..XO.", "..XO."]);
+        let score = score(&board, &Prisoners::default(), 0.5, ScoringMethod::Area);
+        assert_eq!(score.black.stones, 5);
+        assert_eq!(score.black.territory, 10);
+        assert_eq!(score.black.total, 15.0);
+        assert_eq!(score.white.territory, 5);
+        assert_eq!(score.white.total, 10.5);
+        assert_eq!(
+            score.result(),
+            GameResult::Score {
+                winner: Stone::Black,
+                margin: 4.5
+            }
+        );
+    }
+
+    #[test]
+    fn territory_scoring_counts_captures() {
+        let board = board_from(&["..XO.", "..XO.", "..XO.", "..XO.", "..XO."]);
+        let mut prisoners = Prisoners::default();
+        prisoners.record(
+            Stone::White,
+            1,
+            vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)],
+        );
+        let score = score(&board, &prisoners, 0.0, ScoringMethod::Territory);
+        assert_eq!(score.black.total, 10.0);
+        assert_eq!(score.white.captures, 5);
+        assert_eq!(score.white.total, 10.0);
+        assert_eq!(score.result(), GameResult::Draw);
+    }
+
+    #[test]
+    fn seki_eyes_are_neutral_under_territory_scoring() {
+        // The White chain in the top left and the Black chain in the top
+        // right each have one eye and share the liberty between them.
+        let board = board_from(&[
+            ".OO.XX.", //
+            "OOOXXXX", //
+            "XXXOOOO", //
+            "..XO...", //
+            "..XO...", //
+        ]);
+        let territory = classify_territory(&board, ScoringMethod::Territory);
+        assert_eq!(territory[0][0], Some(Territory::Neutral));
+        assert_eq!(territory[0][3], Some(Territory::Neutral));
+        assert_eq!(territory[0][6], Some(Territory::Neutral));
+        assert_eq!(territory[1][0], None);
+        // the walls around the seki still own their territory
+        assert_eq!(territory[3][0], Some(Territory::Black));
+        assert_eq!(territory[4][6], Some(Territory::White));
+
+        let score = score(&board, &Prisoners::default(), 0.0, ScoringMethod::Territory);
+        assert_eq!(score.black.territory, 4);
+        assert_eq!(score.white.territory, 6);
+
+        // area scoring still counts the eyes of the chains in seki
+        let territory = classify_territory(&board, ScoringMethod::Area);
+        assert_eq!(territory[0][0], Some(Territory::White));
+        assert_eq!(territory[0][3], Some(Territory::Neutral));
+        assert_eq!(territory[0][6], Some(Territory::Black));
+    }
+
+    #[test]
+    fn eye_against_no_eye_is_not_seki() {
+        // White has no eye, so Black can fill the shared liberties and
+        // capture; the Black eye is territory.
+        let board = board_from(&[
+            "XO.X.X", //
+            "XO.XXX", //
+            "XXXOOO", //
+            "..XO..", //
+        ]);
+        let territory = classify_territory(&board, ScoringMethod::Territory);
+        assert_eq!(territory[0][4], Some(Territory::Black));
+    }
+
+    #[test]
+    fn region_touching_both_colors_is_neutral() {
+        let board = board_from(&["X.O", "X.O", "X.O"]);
+        let score = score(&board, &Prisoners::default(), 0.0, ScoringMethod::Area);
+        assert_eq!(score.black.territory, 0);
+        assert_eq!(score.white.territory, 0);
+    }
+}
@@ -0,0 +1,101 @@
+//! The stone removal phase played out after both players pass, where the
+//! players agree on which chains are dead before the game is scored.
+use crate::game_logic::{board::Board, prisoners::Prisoners, stone::Stone};
+use std::collections::HashSet;
+
+/// The chains marked dead so far, and which players have accepted them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct StoneRemoval {
+    dead: HashSet<(usize, usize)>,
+    black_accepted: bool,
+    white_accepted: bool,
+}
+
+impl StoneRemoval {
+    /// The points of every stone currently marked dead.
+    pub(crate) fn dead_stones(&self) -> &HashSet<(usize, usize)> {
+        &self.dead
+    }
+
+    /// Mark the chain made of `chain` dead, or alive again if it already is.
+    ///
+    /// Changing the marking withdraws any acceptance given so far, since the
+    /// players agreed to a different set of dead stones.
+    pub(crate) fn toggle_chain(&mut self, chain: Vec<(usize, usize)>) {
+        if chain.iter().all(|pos| self.dead.contains(pos)) {
+            for pos in chain {
+                self.dead.remove(&pos);
+            }
+        } else {
+            self.dead.extend(chain);
+        }
+        self.black_accepted = false;
+        self.white_accepted = false;
+    }
+
+    /// `stone` agrees with the dead stones as they are marked.
+    pub(crate) fn accept(&mut self, stone: Stone) {
+        match stone {
+            Stone::Black => self.black_accepted = true,
+            Stone::White => self.white_accepted = true,
+            Stone::Empty => {}
+        }
+    }
+
+    /// Whether both players agree on the dead stones.
+    pub(crate) fn both_accepted(&self) -> bool {
+        self.black_accepted && self.white_accepted
+    }
+
+    /// The board and prisoners as they will be scored: every dead stone
+    /// is taken off the board and given to its opponent as a prisoner.
+    pub(crate) fn apply(&self, board: &Board, prisoners: &Prisoners) -> (Board, Prisoners) {
+        let mut board = board.clone();
+        let mut prisoners = prisoners.clone();
+        for &(row, col) in &self.dead {
+            if let Some(opponent) = board.state[row][col].get_opponent() {
+                prisoners.add_captures(opponent, 1);
+            }
+            board.state[row][col] = Stone::Empty;
+        }
+        (board, prisoners)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_marks_and_unmarks_chain() {
+        let mut removal = StoneRemoval::default();
+        removal.toggle_chain(vec![(0, 0), (0, 1)]);
+        assert_eq!(removal.dead_stones().len(), 2);
+        removal.toggle_chain(vec![(0, 1), (0, 0)]);
+        assert!(removal.dead_stones().is_empty());
+    }
+
+    #[test]
+    fn toggling_withdraws_acceptance() {
+        let mut removal = StoneRemoval::default();
+        removal.accept(Stone::Black);
+        removal.accept(Stone::White);
+        assert!(removal.both_accepted());
+        removal.toggle_chain(vec![(2, 2)]);
+        assert!(!removal.both_accepted());
+    }
+
+    #[test]
+    fn apply_removes_dead_stones() {
+        let mut board = Board::new(3, 3);
+        board.state[0][0] = Stone::Black;
+        board.state[2][2] = Stone::White;
+        let mut removal = StoneRemoval::default();
+        removal.toggle_chain(vec![(0, 0)]);
+        let (board, prisoners) = removal.apply(&board, &Prisoners::default());
+        assert_eq!(board.state[0][0], Stone::Empty);
+        assert_eq!(board.state[2][2], Stone::White);
+        assert_eq!(prisoners.captured_by(Stone::White), 1);
+        assert_eq!(prisoners.captured_by(Stone::Black), 0);
+    }
+}
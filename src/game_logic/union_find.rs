@@ -5,7 +5,7 @@
 //! module to build the game and keep track of chains of stones efficiently.
 use std::{cmp::Ordering, collections::HashSet};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct UnionFind {
     pub parent: Vec<usize>,
     rank: Vec<usize>, // optimizes "tree" height for quicker lookups.
@@ -41,6 +41,15 @@ impl UnionFind {
         target
     }
 
+    /// The root of the chain `target` belongs to, without compressing the
+    /// path to it, for lookups that only need to read.
+    fn root(&self, mut target: usize) -> usize {
+        while target != self.parent[target] {
+            target = self.parent[target];
+        }
+        target
+    }
+
     pub fn initialize_liberties_of_pos(&mut self, index: usize, libs: Vec<usize>) {
         // only allow for this function to be called when the position is empty.
         // If the position has liberties set already, its in a state of altercation and
@@ -63,6 +72,33 @@ impl UnionFind {
         self.liberties[root_victim].remove(&perpetrator);
     }
 
+    /// Give a liberty back to the chain that `beneficiary` belongs to.
+    /// Used when a neighbouring chain is captured and its points become empty.
+    pub fn add_liberty_to_chain(&mut self, beneficiary: usize, liberty: usize) {
+        let root = self.find(beneficiary);
+        self.liberties[root].insert(liberty);
+    }
+
+    /// Every index that belongs to the same chain as `index`, including itself.
+    pub fn chain_members(&self, index: usize) -> Vec<usize> {
+        let root = self.root(index);
+        (0..self.parent.len())
+            .filter(|&i| self.root(i) == root)
+            .collect()
+    }
+
+    /// Forget everything known about `index`, making it a lone, liberty-less
+    /// position again. Used once the stone on it has been removed from the board.
+    ///
+    /// Only call this for every member of a chain at once, otherwise the
+    /// remaining members could point to a parent that no longer exists.
+    pub fn reset(&mut self, index: usize) {
+        self.parent[index] = index;
+        self.rank[index] = 0;
+        self.size[index] = 1;
+        self.liberties[index].clear();
+    }
+
     pub fn union(&mut self, x: usize, y: usize) {
         let root_x = self.find(x);
         let root_y = self.find(y);
@@ -120,8 +156,14 @@ impl UnionFind {
         self.find(x) == self.find(y)
     }
 
-    pub(crate) fn no_liberties(&self, adjacent_index: usize) -> bool {
-        self.liberties[adjacent_index].is_empty()
+    /// The liberties of the whole chain that `index` belongs to.
+    pub(crate) fn chain_liberties(&self, index: usize) -> &HashSet<usize> {
+        &self.liberties[self.root(index)]
+    }
+
+    pub(crate) fn no_liberties(&mut self, adjacent_index: usize) -> bool {
+        let root = self.find(adjacent_index);
+        self.liberties[root].is_empty()
     }
 }
 
@@ -172,4 +214,24 @@ mod tests {
         let expected_libs = HashSet::from_iter(vec![3, 4, 5]);
         assert_eq!(uf.liberties[0], expected_libs);
     }
+
+    #[test]
+    fn chain_members_and_reset() {
+        use crate::union_find::UnionFind;
+
+        let mut uf = UnionFind::new(3 * 3);
+        uf.initialize_liberties_of_pos(0, vec![1, 3]);
+        uf.initialize_liberties_of_pos(1, vec![2, 4]);
+        uf.union(0, 1);
+        let mut members = uf.chain_members(1);
+        members.sort();
+        assert_eq!(members, vec![0, 1]);
+
+        for member in members {
+            uf.reset(member);
+        }
+        assert_eq!(uf.chain_members(0), vec![0]);
+        assert!(uf.no_liberties(0));
+        assert!(uf.no_liberties(1));
+    }
 }
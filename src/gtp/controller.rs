@@ -0,0 +1,262 @@
+//! Plays against an external GTP engine, like GnuGo or KataGo, run as a
+//! child process.
+use crate::game_logic::{
+    board::Board, error::MoveError, game_move::Play, handicap::fixed_handicap_points,
+    phase::GamePhase, stone::Stone,
+};
+use crate::gtp::{decode_vertex, encode_vertex};
+use crate::ui::{GameEvent, GameView, Player, UserAction};
+use anyhow::{bail, Context, Result};
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// A `Player` whose moves are chosen by a GTP engine.
+///
+/// The engine is told the board once it first has to move or hear a move,
+/// along with any handicap stones, even free ones placed after the game
+/// started, and then hears every move played against it. On its
+/// turns its move comes from `genmove`, and once play ends it accepts the
+/// dead stones its opponent marks.
+///
+/// ```no_run
+/// use go::{game::Game, gtp::GtpPlayer, RawModeUi, Stone};
+/// let engine = GtpPlayer::spawn(Stone::White, "gnugo", &["--mode", "gtp"]).unwrap();
+/// let mut game = Game::new_game(9, 9, RawModeUi::default()).with_player(Stone::White, engine);
+/// game.start_game().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct GtpPlayer {
+    /// The color the engine plays
+    color: Stone,
+    engine: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    /// The board the engine was set up with, once it has been
+    board: Option<Board>,
+    /// The game as last shown, until the engine is set up from it
+    shown: Option<GameView>,
+    /// The phase of the game the engine's turn comes in
+    phase: GamePhase,
+    /// Set while the engine's last `genmove` has not been played in the game yet
+    awaiting_move: bool,
+}
+
+impl GtpPlayer {
+    /// Start the engine `program` with `args`, to play `color`.
+    pub fn spawn(color: Stone, program: impl AsRef<OsStr>, args: &[&str]) -> Result<Self> {
+        let program = program.as_ref();
+        let mut engine = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Could not start GTP engine {program:?}"))?;
+        let stdin = engine.stdin.take().context("GTP engine has no stdin")?;
+        let stdout = BufReader::new(engine.stdout.take().context("GTP engine has no stdout")?);
+        let mut player = GtpPlayer {
+            color,
+            engine,
+            stdin,
+            stdout,
+            board: None,
+            shown: None,
+            phase: GamePhase::Playing,
+            awaiting_move: false,
+        };
+        let version = player.command("protocol_version")?;
+        if version != "2" {
+            bail!("GTP engine speaks protocol version {version}, not 2");
+        }
+        Ok(player)
+    }
+
+    /// Send `command` to the engine and wait for its response.
+    fn command(&mut self, command: &str) -> Result<String> {
+        writeln!(self.stdin, "{command}")?;
+        self.stdin.flush()?;
+        let mut response = String::new();
+        loop {
+            let mut line = String::new();
+            if self.stdout.read_line(&mut line)? == 0 {
+                bail!("GTP engine quit while answering '{command}'");
+            }
+            // the response ends with an empty line
+            if line.trim().is_empty() && !response.is_empty() {
+                break;
+            }
+            response.push_str(&line);
+        }
+        parse_response(&response).with_context(|| format!("GTP engine refused '{command}'"))
+    }
+
+    /// Set the engine up with the board and komi the game starts from. Only
+    /// square boards work with GTP, and the board has to be empty or hold
+    /// Black's handicap stones.
+    fn set_up(&mut self, game: &GameView) -> Result<()> {
+        let board = &game.board;
+        if board.width != board.height {
+            bail!("GTP engines only play on square boards");
+        }
+        self.command(&format!("boardsize {}", board.width))?;
+        self.command("clear_board")?;
+        self.command(&format!("komi {}", game.komi))?;
+        let mut handicap = Vec::new();
+        for (row, stones) in board.state.iter().enumerate() {
+            for (col, stone) in stones.iter().enumerate() {
+                match stone {
+                    Stone::Black => handicap.push((row, col)),
+                    Stone::White => bail!("A GTP engine can only join a game before it starts"),
+                    Stone::Empty => {}
+                }
+            }
+        }
+        if !handicap.is_empty() {
+            // stones on the star points are a fixed handicap, which more
+            // engines know than a free one
+            let mut fixed = fixed_handicap_points(board.width, board.height, handicap.len())
+                .unwrap_or_default();
+            fixed.sort();
+            if fixed == handicap {
+                self.command(&format!("fixed_handicap {}", handicap.len()))?;
+            } else {
+                let vertices: Vec<String> = handicap
+                    .into_iter()
+                    .filter_map(|pos| encode_vertex(pos, board.height))
+                    .collect();
+                self.command(&format!("set_free_handicap {}", vertices.join(" ")))?;
+            }
+        }
+        self.board = Some(board.clone());
+        Ok(())
+    }
+
+    /// Set the engine up from the game as last shown, unless it already is.
+    /// This waits for the engine's first move or the first move it hears, so
+    /// that Black has placed any free handicap stones by then.
+    fn ensure_set_up(&mut self) -> Result<&Board> {
+        if self.board.is_none() {
+            let shown = self
+                .shown
+                .take()
+                .context("The game has not been shown yet")?;
+            self.set_up(&shown)?;
+        }
+        Ok(self.board.as_ref().unwrap())
+    }
+
+    /// Ask the engine for its move.
+    fn genmove(&mut self) -> Result<UserAction> {
+        let board = self.ensure_set_up()?.clone();
+        let vertex = self.command(&format!("genmove {}", color_name(self.color)))?;
+        self.awaiting_move = true;
+        match vertex.to_ascii_lowercase().as_str() {
+            "pass" => Ok(UserAction::Pass),
+            "resign" => Ok(UserAction::Resign),
+            _ => match decode_vertex(&vertex, board.width, board.height)
+                .and_then(|(row, col)| board.point(row, col).ok())
+            {
+                Some(point) => Ok(UserAction::Move(point)),
+                None => bail!("GTP engine played '{vertex}', which is not on the board"),
+            },
+        }
+    }
+}
+
+impl Player for GtpPlayer {
+    fn next_action(&mut self, _board: &Board) -> Result<UserAction> {
+        match self.phase {
+            // the engine goes along with the dead stones its opponent marks
+            GamePhase::Scoring => Ok(UserAction::Accept),
+            _ => self.genmove(),
+        }
+    }
+
+    fn see_game(&mut self, game: &GameView) -> Result<()> {
+        if self.board.is_none() {
+            self.shown = Some(game.clone());
+        }
+        Ok(())
+    }
+
+    fn action_refused(&mut self, err: &MoveError) -> Result<()> {
+        bail!("GTP engine played a move the rules refuse: {err}")
+    }
+
+    fn hear(&mut self, event: &GameEvent) -> Result<()> {
+        match event {
+            GameEvent::ToMove { phase, .. } => self.phase = *phase,
+            GameEvent::Played(play) if play.stone() == self.color && self.awaiting_move => {
+                // the engine already knows the move it generated
+                self.awaiting_move = false;
+            }
+            GameEvent::Played(play) => {
+                let height = self.ensure_set_up()?.height;
+                let vertex = match play {
+                    Play::Stone(mv) => encode_vertex(mv.pos.into(), height)
+                        .context("The move cannot be named in GTP")?,
+                    Play::Pass { .. } => "pass".to_string(),
+                };
+                self.command(&format!("play {} {vertex}", color_name(play.stone())))?;
+            }
+            GameEvent::Undone(_) => {
+                self.command("undo")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for GtpPlayer {
+    fn drop(&mut self) {
+        // the engine is done either way, so a failure to quit cleanly is ignored
+        let _ = writeln!(self.stdin, "quit").and_then(|_| self.stdin.flush());
+        let _ = self.engine.wait();
+    }
+}
+
+/// The GTP name of a color.
+fn color_name(stone: Stone) -> &'static str {
+    if stone == Stone::Black {
+        "black"
+    } else {
+        "white"
+    }
+}
+
+/// The text of a GTP response, or an error if the engine reported one.
+fn parse_response(response: &str) -> Result<String> {
+    let response = response.trim();
+    // skip the id the command may have been given
+    let status = response.chars().next();
+    let text = response
+        .get(1..)
+        .unwrap_or_default()
+        .trim_start_matches(|ch: char| ch.is_ascii_digit())
+        .trim()
+        .to_string();
+    match status {
+        Some('=') => Ok(text),
+        Some('?') => bail!("{text}"),
+        _ => bail!("'{response}' is not a GTP response"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn responses() {
+        assert_eq!(parse_response("= D4\n").unwrap(), "D4");
+        assert_eq!(parse_response("=12 pass\n").unwrap(), "pass");
+        assert_eq!(parse_response("= \n").unwrap(), "");
+        assert_eq!(
+            parse_response("= \n  A B\n 2 . . 2\n").unwrap(),
+            "A B\n 2 . . 2"
+        );
+        let err = parse_response("?3 illegal move\n").unwrap_err();
+        assert_eq!(err.to_string(), "illegal move");
+        parse_response("D4").unwrap_err();
+    }
+}
@@ -0,0 +1,355 @@
+//! Runs the crate as a GTP engine, answering the commands of a controller.
+use crate::ai;
+use crate::game_logic::{board::Board, game::Game, handicap::Handicap, stone::Stone};
+use crate::gtp::{decode_vertex, encode_vertex, MAX_SIZE};
+use crate::ui::{GameView, UserAction, UserInterface};
+use anyhow::Result;
+use std::io::{BufRead, Write};
+
+/// Every command the engine knows, as listed by `list_commands`.
+const COMMANDS: &[&str] = &[
+    "boardsize",
+    "clear_board",
+    "final_score",
+    "fixed_handicap",
+    "genmove",
+    "known_command",
+    "komi",
+    "list_commands",
+    "name",
+    "play",
+    "protocol_version",
+    "quit",
+    "set_free_handicap",
+    "showboard",
+    "undo",
+    "version",
+];
+
+/// The engine plays the moves it is told itself, so its game never asks for input.
+#[derive(Debug, Default)]
+struct Headless;
+
+impl UserInterface for Headless {
+    fn input(&mut self, _board: &Board) -> Result<UserAction> {
+        Ok(UserAction::Quit)
+    }
+
+    fn view(&mut self, _game: &GameView) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A GTP engine reading commands from `input` and writing its responses to
+/// `output`, playing a game on a 19x19 board until told otherwise.
+///
+/// ```rust
+/// use go::gtp::GtpEngine;
+/// let commands = "boardsize 9\nplay black E5\ngenmove white\n";
+/// let mut responses = Vec::new();
+/// GtpEngine::new(commands.as_bytes(), &mut responses).run().unwrap();
+/// assert!(String::from_utf8(responses).unwrap().starts_with("= \n\n= \n\n= "));
+/// ```
+pub struct GtpEngine<R, W> {
+    input: R,
+    output: W,
+    game: Game<Headless>,
+    /// Komi set by the controller, which is kept when the board is cleared
+    komi: Option<f32>,
+}
+
+impl<R: BufRead, W: Write> GtpEngine<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        GtpEngine {
+            input,
+            output,
+            game: Game::new_game(19, 19, Headless),
+            komi: None,
+        }
+    }
+
+    /// Answer commands until the controller sends `quit` or closes the input.
+    pub fn run(&mut self) -> Result<()> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            // comments and control characters are dropped, and tabs are spaces
+            let line: String = line
+                .split('#')
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .filter(|ch| !ch.is_control() || *ch == '\t')
+                .map(|ch| if ch == '\t' { ' ' } else { ch })
+                .collect();
+            let mut words = line.split_whitespace().peekable();
+            let id = words
+                .next_if(|word| word.chars().all(|ch| ch.is_ascii_digit()))
+                .unwrap_or_default();
+            let Some(command) = words.next() else {
+                continue;
+            };
+            let args: Vec<&str> = words.collect();
+            match self.execute(command, &args) {
+                Ok(response) => write!(self.output, "={id} {response}\n\n")?,
+                Err(message) => write!(self.output, "?{id} {message}\n\n")?,
+            }
+            self.output.flush()?;
+            if command == "quit" {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Carry out a single command, giving the response to send back, or the
+    /// error message if the command failed.
+    fn execute(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        let syntax_error = || "syntax error".to_string();
+        match command {
+            "protocol_version" => Ok("2".to_string()),
+            "name" => Ok(env!("CARGO_PKG_NAME").to_string()),
+            "version" => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            "known_command" => {
+                let name = args.first().ok_or_else(syntax_error)?;
+                Ok(COMMANDS.contains(name).to_string())
+            }
+            "list_commands" => Ok(COMMANDS.join("\n")),
+            "quit" => Ok(String::new()),
+            "boardsize" => {
+                let size: usize = parse_arg(args, 0)?;
+                if !(2..=MAX_SIZE).contains(&size) {
+                    return Err("unacceptable size".to_string());
+                }
+                self.new_game(size, size);
+                Ok(String::new())
+            }
+            "clear_board" => {
+                self.new_game(self.game.board().width, self.game.board().height);
+                Ok(String::new())
+            }
+            "komi" => {
+                let komi: f32 = parse_arg(args, 0)?;
+                self.komi = Some(komi);
+                // the komi applies to the game in progress too
+                self.game.set_komi(komi);
+                Ok(String::new())
+            }
+            "fixed_handicap" => self.fixed_handicap(parse_arg(args, 0)?),
+            "set_free_handicap" => self.set_free_handicap(args),
+            "play" => {
+                let stone = parse_color(args.first().ok_or_else(syntax_error)?)?;
+                let vertex = args.get(1).ok_or_else(syntax_error)?;
+                let pos = if vertex.eq_ignore_ascii_case("pass") {
+                    None
+                } else {
+                    let board = self.game.board();
+                    let point = decode_vertex(vertex, board.width, board.height)
+                        .and_then(|(row, col)| board.point(row, col).ok());
+                    Some(point.ok_or_else(syntax_error)?)
+                };
+                self.game
+                    .play(stone, pos)
+                    .map_err(|_| "illegal move".to_string())?;
+                Ok(String::new())
+            }
+            "genmove" => {
+                let stone = parse_color(args.first().ok_or_else(syntax_error)?)?;
+                let played = ai::generate_move(self.game.position(), stone);
+                self.game
+                    .play(stone, played)
+                    .map_err(|err| err.to_string())?;
+                Ok(played
+                    .and_then(|point| encode_vertex(point.into(), self.game.board().height))
+                    .unwrap_or_else(|| "pass".to_string()))
+            }
+            "undo" => {
+                self.game.undo().map_err(|_| "cannot undo".to_string())?;
+                Ok(String::new())
+            }
+            "showboard" => Ok(format!("\n{}", self.showboard())),
+            "final_score" => Ok(self.game.score().result().to_string()),
+            _ => Err("unknown command".to_string()),
+        }
+    }
+
+    /// Start a new game on an empty board, keeping the komi.
+    fn new_game(&mut self, width: usize, height: usize) {
+        self.game = self.empty_game(width, height);
+    }
+
+    /// A game on an empty board, with the komi the controller asked for.
+    fn empty_game(&self, width: usize, height: usize) -> Game<Headless> {
+        let game = Game::new_game(width, height, Headless);
+        match self.komi {
+            Some(komi) => game.with_komi(komi),
+            None => game,
+        }
+    }
+
+    /// Put `stones` handicap stones on the star points of an empty board,
+    /// responding with where they went.
+    fn fixed_handicap(&mut self, stones: usize) -> Result<String, String> {
+        if !self.game.moves().is_empty() || self.game.handicap().is_some() {
+            return Err("board not empty".to_string());
+        }
+        let (width, height) = (self.game.board().width, self.game.board().height);
+        self.game = self
+            .empty_game(width, height)
+            .with_handicap(Handicap::Fixed(stones))
+            .map_err(|_| "invalid number of stones".to_string())?;
+        Ok(self
+            .game
+            .handicap_stones()
+            .iter()
+            .filter_map(|&pos| encode_vertex(pos, height))
+            .collect::<Vec<_>>()
+            .join(" "))
+    }
+
+    /// Put Black's handicap stones on the `vertices` the controller chose,
+    /// on an empty board.
+    fn set_free_handicap(&mut self, vertices: &[&str]) -> Result<String, String> {
+        if !self.game.moves().is_empty() || self.game.handicap().is_some() {
+            return Err("board not empty".to_string());
+        }
+        let bad_vertices = || "bad vertex list".to_string();
+        let (width, height) = (self.game.board().width, self.game.board().height);
+        let mut game = self
+            .empty_game(width, height)
+            .with_handicap(Handicap::Free(vertices.len()))
+            .map_err(|_| bad_vertices())?;
+        for vertex in vertices {
+            let point = decode_vertex(vertex, width, height)
+                .and_then(|(row, col)| game.board().point(row, col).ok())
+                .ok_or_else(bad_vertices)?;
+            // a point given twice is no longer empty
+            game.play(Stone::Black, Some(point))
+                .map_err(|_| bad_vertices())?;
+        }
+        self.game = game;
+        Ok(String::new())
+    }
+
+    /// The board drawn with `X` for Black and `O` for White, labelled with
+    /// GTP coordinates.
+    fn showboard(&self) -> String {
+        let board = self.game.board();
+        let letters: String = (0..board.width)
+            .filter_map(|col| encode_vertex((board.height - 1, col), board.height))
+            .map(|vertex| format!(" {}", &vertex[..1]))
+            .collect();
+        let mut text = format!("   {letters}\n");
+        for (row, stones) in board.state.iter().enumerate() {
+            let number = board.height - row;
+            text.push_str(&format!("{number:>2} "));
+            for stone in stones {
+                text.push_str(match stone {
+                    Stone::Black => " X",
+                    Stone::White => " O",
+                    Stone::Empty => " .",
+                });
+            }
+            text.push_str(&format!(" {number}\n"));
+        }
+        text.push_str(&format!("   {letters}\n"));
+        text.push_str(&self.game.prisoners().to_string());
+        text
+    }
+}
+
+/// Parse argument number `index` of a command.
+fn parse_arg<T: std::str::FromStr>(args: &[&str], index: usize) -> Result<T, String> {
+    args.get(index)
+        .and_then(|arg| arg.parse().ok())
+        .ok_or_else(|| "syntax error".to_string())
+}
+
+/// Parse a GTP color, `b`, `w`, `black` or `white` in any case.
+fn parse_color(color: &str) -> Result<Stone, String> {
+    match color.to_ascii_lowercase().as_str() {
+        "b" | "black" => Ok(Stone::Black),
+        "w" | "white" => Ok(Stone::White),
+        _ => Err("syntax error".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(commands: &str) -> String {
+        let mut output = Vec::new();
+        GtpEngine::new(commands.as_bytes(), &mut output)
+            .run()
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn ids_and_errors() {
+        assert_eq!(
+            run("1 protocol_version\n# a comment\n\n2 frobnicate\nname\n"),
+            "=1 2\n\n?2 unknown command\n\n= go\n\n"
+        );
+    }
+
+    #[test]
+    fn known_commands() {
+        let output = run("known_command genmove\nknown_command kgs-chat\nlist_commands\n");
+        assert!(output.starts_with("= true\n\n= false\n\n= boardsize\nclear_board\n"));
+    }
+
+    #[test]
+    fn play_and_showboard() {
+        let output = run("boardsize 3\nkomi 0.5\nplay b B2\nplay w a3\nplay b b2\nshowboard\n");
+        assert_eq!(
+            output,
+            "= \n\n= \n\n= \n\n= \n\n? illegal move\n\n= \n    A B C\n 3  O . . 3\n 2  . X . 2\n 1  . . . 1\n    A B C\nCaptures - Black: 0  White: 0\n\n"
+        );
+    }
+
+    #[test]
+    fn quit_stops_reading() {
+        assert_eq!(run("quit\nname\n"), "= \n\n");
+    }
+
+    #[test]
+    fn boardsize_limits() {
+        assert_eq!(
+            run("boardsize 26\nboardsize 1\nboardsize x\n"),
+            "? unacceptable size\n\n? unacceptable size\n\n? syntax error\n\n"
+        );
+    }
+
+    #[test]
+    fn fixed_handicap_and_score() {
+        let output = run("boardsize 9\nfixed_handicap 2\nfixed_handicap 2\nfinal_score\n");
+        assert_eq!(output, "= \n\n= C3 G7\n\n? board not empty\n\n= B+78.5\n\n");
+        let output = run("boardsize 9\nfixed_handicap 10\nfixed_handicap 3\n");
+        assert_eq!(output, "= \n\n? invalid number of stones\n\n= C3 G7 C7\n\n");
+    }
+
+    #[test]
+    fn free_handicap() {
+        let output = run(
+            "boardsize 5\nset_free_handicap A1\nset_free_handicap A1 A1\nset_free_handicap A1 Z9\n\
+             set_free_handicap A1 B2\nset_free_handicap C3 D4\nplay w C3\nshowboard\n",
+        );
+        assert_eq!(
+            output,
+            "= \n\n? bad vertex list\n\n? bad vertex list\n\n? bad vertex list\n\n= \n\n? board not empty\n\n= \n\n= \n    A B C D E\n 5  . . . . . 5\n 4  . . . . . 4\n 3  . . O . . 3\n 2  . X . . . 2\n 1  X . . . . 1\n    A B C D E\nCaptures - Black: 0  White: 0\n\n"
+        );
+    }
+
+    #[test]
+    fn genmove_and_undo() {
+        let output = run("boardsize 5\ngenmove black\nundo\nundo\ngenmove w\nshowboard\n");
+        assert_eq!(
+            output,
+            "= \n\n= C3\n\n= \n\n? cannot undo\n\n= C3\n\n= \n    A B C D E\n 5  . . . . . 5\n 4  . . . . . 4\n 3  . . O . . 3\n 2  . . . . . 2\n 1  . . . . . 1\n    A B C D E\nCaptures - Black: 0  White: 0\n\n"
+        );
+    }
+}
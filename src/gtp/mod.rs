@@ -0,0 +1,18 @@
+//! The Go Text Protocol (GTP, version 2), which lets GUIs like Sabaki and
+//! tools like gogui-twogtp play against this crate, over stdin and stdout,
+//! and lets this crate play against engines like GnuGo and KataGo.
+//!
+//! GTP names points with vertices like `D4`: a column letter from `A`,
+//! skipping `I`, then the row number counted from 1 at the bottom of the board.
+
+mod controller;
+mod engine;
+
+pub use crate::game_logic::notation::{decode_vertex, encode_vertex};
+pub use controller::GtpPlayer;
+pub use engine::GtpEngine;
+
+use crate::game_logic::notation::GTP_COLUMNS;
+
+/// The widest board GTP can name every point of.
+pub const MAX_SIZE: usize = GTP_COLUMNS.len();
@@ -5,12 +5,26 @@
 //! - TUI so you can play from your terminal
 //! - Integration with OGS(in progress)
 
+mod ai;
 mod game_logic;
+pub mod gtp;
+pub mod sgf;
 mod ui;
 
 pub(crate) use game_logic::union_find;
 
-pub use game_logic::board::Board;
+pub use game_logic::board::{Board, Point};
+pub use game_logic::error::MoveError;
 pub use game_logic::game;
+pub use game_logic::game_move::{GameMove, Play};
+pub use game_logic::handicap::{fixed_handicap_points, Handicap};
+pub use game_logic::notation::Notation;
+pub use game_logic::overlay::{Mark, Overlay};
+pub use game_logic::phase::{GamePhase, GameResult};
+pub use game_logic::position::{MoveOutcome, Position};
+pub use game_logic::prisoners::Prisoners;
+pub use game_logic::rules::{HandicapCompensation, KoRule, Ruleset};
+pub use game_logic::scoring::{self, PlayerScore, Score, ScoringMethod, Territory};
 pub use game_logic::stone::Stone;
+pub use sgf::SgfError;
 pub use ui::*;
@@ -1,8 +1,34 @@
 use anyhow::Result;
-use go::{game::Game, *};
+use go::{game::Game, gtp::GtpEngine, *};
+use std::io;
 
 fn main() -> Result<()> {
-    let mut game: Game<RawModeUi> = Game::new_game(9, 9, Default::default());
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("--gtp") {
+        // let a GTP controller drive the game over stdin and stdout
+        return GtpEngine::new(io::stdin().lock(), io::stdout()).run();
+    }
+    // points are named like GTP and OGS do, e.g. D4, instead of c12
+    let notation = match args.iter().position(|arg| arg == "--gtp-notation") {
+        Some(index) => {
+            args.remove(index);
+            Notation::Gtp
+        }
+        None => Notation::RowLetter,
+    };
+
+    // an SGF file given on the command line is loaded to play on from
+    let ui = RawModeUi::default().with_notation(notation);
+    let mut game: Game<RawModeUi> = match args.into_iter().next() {
+        Some(path) => Game::load_sgf(path, ui)?,
+        None => Game::new_game(9, 9, ui),
+    };
     game.start_game()?;
+    let result = game.result();
+    // leave the alternate screen before printing the result
+    drop(game);
+    if let Some(result) = result {
+        println!("Result: {result}");
+    }
     Ok(())
 }
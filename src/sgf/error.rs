@@ -0,0 +1,33 @@
+//! Errors for SGF records that cannot be read or replayed.
+use std::fmt::Display;
+
+/// The reason an SGF record could not be loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SgfError {
+    /// The text is not valid SGF. `offset` is the byte offset of the problem.
+    Syntax { offset: usize, reason: String },
+    /// The property `ident` of node number `node`, counting the root node
+    /// as 0, has a bad value or describes a move the rules do not allow.
+    Property {
+        node: usize,
+        ident: String,
+        reason: String,
+    },
+}
+
+impl Display for SgfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SgfError::Syntax { offset, reason } => {
+                write!(f, "Malformed SGF at byte {offset}: {reason}")
+            }
+            SgfError::Property {
+                node,
+                ident,
+                reason,
+            } => write!(f, "SGF node {node}, property {ident}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for SgfError {}
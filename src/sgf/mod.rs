@@ -0,0 +1,308 @@
+//! Reading and writing games in the Smart Game Format (SGF, FF[4]), the
+//! file format every other Go program understands.
+//!
+//! A game record is a tree of nodes, each holding properties like `B[dd]`
+//! (a Black move) or `KM[6.5]` (komi), where every branch of the tree is a
+//! variation of the game. Points are written as two
+//! letters, the column then the row, counted from the top left corner with
+//! `a`-`z` followed by `A`-`Z`, so boards can be up to 52 points wide.
+use std::fmt::{Display, Write};
+
+mod error;
+mod parser;
+mod tree;
+
+pub use error::SgfError;
+pub use parser::parse;
+pub use tree::{GameTree, NodeId};
+
+/// A single SGF property, an identifier like `AB` with one or more values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Property {
+    pub ident: String,
+    pub values: Vec<String>,
+}
+
+/// A node of a game record, holding its properties in the order they were added.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Node {
+    pub properties: Vec<Property>,
+}
+
+/// A mark drawn on a point of the board to point something out, like the
+/// `TR`, `SQ` and `LB` properties of a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Markup {
+    Triangle((usize, usize)),
+    Square((usize, usize)),
+    /// A short text written on the point.
+    Label((usize, usize), String),
+}
+
+impl Node {
+    /// Add the property `ident` with the given values to the node.
+    pub fn push(&mut self, ident: &str, values: Vec<String>) {
+        self.properties.push(Property {
+            ident: ident.to_string(),
+            values,
+        });
+    }
+
+    /// The values of the property `ident`, if the node has it.
+    pub fn get(&self, ident: &str) -> Option<&[String]> {
+        self.properties
+            .iter()
+            .find(|prop| prop.ident == ident)
+            .map(|prop| prop.values.as_slice())
+    }
+
+    /// Set the property `ident` to the given values, replacing it if the
+    /// node already has it.
+    pub fn set(&mut self, ident: &str, values: Vec<String>) {
+        match self.properties.iter_mut().find(|prop| prop.ident == ident) {
+            Some(prop) => prop.values = values,
+            None => self.push(ident, values),
+        }
+    }
+
+    /// The comment on this node (`C`).
+    pub fn comment(&self) -> Option<&str> {
+        self.get("C")?.first().map(String::as_str)
+    }
+
+    /// Comment on this node, replacing any earlier comment.
+    pub fn set_comment(&mut self, comment: &str) {
+        self.set("C", vec![comment.to_string()]);
+    }
+
+    /// The name of this node (`N`), often used to name a variation.
+    pub fn name(&self) -> Option<&str> {
+        self.get("N")?.first().map(String::as_str)
+    }
+
+    /// Name this node.
+    pub fn set_name(&mut self, name: &str) {
+        self.set("N", vec![name.to_string()]);
+    }
+
+    /// The marks drawn on the board at this node. Values that are not
+    /// points are skipped.
+    pub fn markup(&self) -> Vec<Markup> {
+        let values = |ident| self.get(ident).unwrap_or_default().iter();
+        let points = |ident| {
+            values(ident)
+                .filter_map(|value| decode_point_list(value))
+                .flatten()
+        };
+        let labels = values("LB").filter_map(|value| {
+            let (point, text) = value.split_once(':')?;
+            Some(Markup::Label(decode_point(point)?, text.to_string()))
+        });
+        points("TR")
+            .map(Markup::Triangle)
+            .chain(points("SQ").map(Markup::Square))
+            .chain(labels)
+            .collect()
+    }
+
+    /// Draw `markup` on the board at this node.
+    pub fn add_markup(&mut self, markup: Markup) {
+        let (ident, value) = match markup {
+            Markup::Triangle(pos) => ("TR", encode_point(pos)),
+            Markup::Square(pos) => ("SQ", encode_point(pos)),
+            Markup::Label(pos, text) => ("LB", encode_point(pos).map(|point| point + ":" + &text)),
+        };
+        let Some(value) = value else {
+            return;
+        };
+        match self.properties.iter_mut().find(|prop| prop.ident == ident) {
+            Some(prop) => prop.values.push(value),
+            None => self.push(ident, vec![value]),
+        }
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_char(';')?;
+        for prop in &self.properties {
+            f.write_str(&prop.ident)?;
+            for value in &prop.values {
+                write!(f, "[{}]", escape(value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The SGF letter for a row or column, `None` if it is past the 52 SGF allows.
+fn coordinate(index: usize) -> Option<char> {
+    match index {
+        0..=25 => Some((b'a' + index as u8) as char),
+        26..=51 => Some((b'A' + (index - 26) as u8) as char),
+        _ => None,
+    }
+}
+
+/// The SGF point for `pos`, given as `(row, col)` with row 0 at the top.
+pub fn encode_point(pos: (usize, usize)) -> Option<String> {
+    let (row, col) = pos;
+    Some([coordinate(col)?, coordinate(row)?].iter().collect())
+}
+
+/// The row or column for an SGF letter.
+fn index(coordinate: char) -> Option<usize> {
+    match coordinate {
+        'a'..='z' => Some(coordinate as usize - 'a' as usize),
+        'A'..='Z' => Some(coordinate as usize - 'A' as usize + 26),
+        _ => None,
+    }
+}
+
+/// The `(row, col)` of an SGF point, the opposite of `encode_point`.
+pub fn decode_point(point: &str) -> Option<(usize, usize)> {
+    let mut chars = point.chars();
+    let col = index(chars.next()?)?;
+    let row = index(chars.next()?)?;
+    chars.next().is_none().then_some((row, col))
+}
+
+/// The points of a point list value: a single point, or every point of the
+/// rectangle between two corners like `aa:cc`.
+pub fn decode_point_list(value: &str) -> Option<Vec<(usize, usize)>> {
+    let (first, last) = match value.split_once(':') {
+        Some((first, last)) => (decode_point(first)?, decode_point(last)?),
+        None => (decode_point(value)?, decode_point(value)?),
+    };
+    let rows = first.0.min(last.0)..=first.0.max(last.0);
+    let cols = first.1.min(last.1)..=first.1.max(last.1);
+    Some(
+        rows.flat_map(|row| cols.clone().map(move |col| (row, col)))
+            .collect(),
+    )
+}
+
+/// The value of `SZ` for a board: just the size for square boards, and
+/// `width:height` otherwise.
+pub fn encode_size(width: usize, height: usize) -> String {
+    if width == height {
+        width.to_string()
+    } else {
+        format!("{width}:{height}")
+    }
+}
+
+/// The `(width, height)` given by a value of `SZ`.
+pub fn decode_size(size: &str) -> Option<(usize, usize)> {
+    let parse = |n: &str| n.trim().parse().ok().filter(|n| (1..=52).contains(n));
+    match size.split_once(':') {
+        Some((width, height)) => Some((parse(width)?, parse(height)?)),
+        None => parse(size).map(|n| (n, n)),
+    }
+}
+
+/// Escape the characters SGF gives meaning to inside a property value.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if ch == ']' || ch == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_are_column_then_row() {
+        assert_eq!(encode_point((0, 0)).unwrap(), "aa");
+        assert_eq!(encode_point((2, 6)).unwrap(), "gc");
+        assert_eq!(encode_point((18, 3)).unwrap(), "ds");
+        assert_eq!(encode_point((30, 51)).unwrap(), "ZE");
+        assert!(encode_point((52, 0)).is_none());
+    }
+
+    #[test]
+    fn decoding_points() {
+        assert_eq!(decode_point("gc"), Some((2, 6)));
+        assert_eq!(decode_point("ZE"), Some((30, 51)));
+        for pos in [(0, 0), (18, 3), (40, 27)] {
+            assert_eq!(decode_point(&encode_point(pos).unwrap()), Some(pos));
+        }
+        assert_eq!(decode_point(""), None);
+        assert_eq!(decode_point("a"), None);
+        assert_eq!(decode_point("a1"), None);
+        assert_eq!(decode_point("abc"), None);
+    }
+
+    #[test]
+    fn rectangular_sizes() {
+        assert_eq!(encode_size(19, 19), "19");
+        assert_eq!(encode_size(9, 13), "9:13");
+        assert_eq!(decode_size("19"), Some((19, 19)));
+        assert_eq!(decode_size("9:13"), Some((9, 13)));
+        assert_eq!(decode_size("0"), None);
+        assert_eq!(decode_size("x"), None);
+    }
+
+    #[test]
+    fn values_are_escaped() {
+        let mut node = Node::default();
+        node.push("PB", vec![r"a]b\c".to_string()]);
+        node.push("AB", vec!["aa".to_string(), "bb".to_string()]);
+        assert_eq!(node.to_string(), r";PB[a\]b\\c]AB[aa][bb]");
+        assert_eq!(node.get("AB").unwrap(), ["aa", "bb"]);
+        assert!(node.get("AW").is_none());
+    }
+
+    #[test]
+    fn point_lists() {
+        assert_eq!(decode_point_list("bb"), Some(vec![(1, 1)]));
+        assert_eq!(
+            decode_point_list("bb:ac"),
+            Some(vec![(1, 0), (1, 1), (2, 0), (2, 1)])
+        );
+        assert_eq!(decode_point_list("bb:"), None);
+    }
+
+    #[test]
+    fn comments_and_markup() {
+        let mut node = Node::default();
+        node.set_comment("first");
+        node.set_comment("a good move");
+        node.set_name("joseki");
+        node.add_markup(Markup::Triangle((0, 0)));
+        node.add_markup(Markup::Label((2, 1), "A".to_string()));
+        node.add_markup(Markup::Triangle((1, 1)));
+        node.add_markup(Markup::Square((3, 3)));
+        assert_eq!(node.comment(), Some("a good move"));
+        assert_eq!(node.name(), Some("joseki"));
+        assert_eq!(
+            node.to_string(),
+            ";C[a good move]N[joseki]TR[aa][bb]LB[bc:A]SQ[dd]"
+        );
+        assert_eq!(
+            node.markup(),
+            vec![
+                Markup::Triangle((0, 0)),
+                Markup::Triangle((1, 1)),
+                Markup::Square((3, 3)),
+                Markup::Label((2, 1), "A".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn properties_without_values() {
+        let mut node = Node::default();
+        node.push("C", vec![]);
+        node.push("N", vec![]);
+        assert_eq!(node.comment(), None);
+        assert_eq!(node.name(), None);
+        assert_eq!(node.get("C"), Some(&[][..]));
+    }
+}
@@ -0,0 +1,202 @@
+//! A parser for the text of SGF records.
+use crate::sgf::{error::SgfError, GameTree, Node, NodeId};
+use std::{iter::Peekable, str::CharIndices};
+
+/// Parse the first game of an SGF collection into its tree of nodes, keeping
+/// every variation and property.
+pub fn parse(text: &str) -> Result<GameTree, SgfError> {
+    let mut parser = Parser {
+        chars: text.char_indices().peekable(),
+        len: text.len(),
+    };
+    parser.skip_whitespace();
+    let mut tree = None;
+    parser.game_tree(&mut tree, None)?;
+    tree.ok_or_else(|| parser.error("the game has no nodes"))
+}
+
+struct Parser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    len: usize,
+}
+
+impl Parser<'_> {
+    /// Parse `( Sequence { GameTree } )`, adding its nodes to `tree` after
+    /// `parent`. The first node parsed becomes the root of the tree.
+    fn game_tree(
+        &mut self,
+        tree: &mut Option<GameTree>,
+        mut parent: Option<NodeId>,
+    ) -> Result<(), SgfError> {
+        self.expect('(')?;
+        if self.peek() != Some(';') {
+            return Err(self.error("a game tree must start with a node"));
+        }
+        while self.peek() == Some(';') {
+            let node = self.node()?;
+            parent = Some(match (tree.as_mut(), parent) {
+                (Some(tree), Some(parent)) => tree.add_child(parent, node),
+                _ => {
+                    *tree = Some(GameTree::new(node));
+                    GameTree::ROOT
+                }
+            });
+        }
+        while self.peek() == Some('(') {
+            self.game_tree(tree, parent)?;
+        }
+        self.expect(')')
+    }
+
+    /// Parse `; { Property }`.
+    fn node(&mut self) -> Result<Node, SgfError> {
+        self.expect(';')?;
+        let mut node = Node::default();
+        while self.peek().is_some_and(|ch| ch.is_ascii_alphabetic()) {
+            let ident = self.prop_ident();
+            if ident.is_empty() {
+                return Err(self.error("property identifiers must have capital letters"));
+            }
+            if self.peek() != Some('[') {
+                return Err(self.error(&format!("property {ident} has no value")));
+            }
+            let mut values = Vec::new();
+            while self.peek() == Some('[') {
+                values.push(self.prop_value()?);
+            }
+            node.push(&ident, values);
+        }
+        Ok(node)
+    }
+
+    /// Parse a property identifier. Lower case letters, which older versions
+    /// of SGF allowed in identifiers, are dropped.
+    fn prop_ident(&mut self) -> String {
+        let mut ident = String::new();
+        while let Some(ch) = self.peek().filter(|ch| ch.is_ascii_alphabetic()) {
+            if ch.is_ascii_uppercase() {
+                ident.push(ch);
+            }
+            self.chars.next();
+        }
+        self.skip_whitespace();
+        ident
+    }
+
+    /// Parse `[ value ]`, removing escapes and soft line breaks.
+    fn prop_value(&mut self) -> Result<String, SgfError> {
+        self.expect_raw('[')?;
+        let mut value = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, ']')) => break,
+                Some((_, '\\')) => match self.chars.next() {
+                    // an escaped line break is a soft line break, which is removed
+                    Some((_, '\n')) => {
+                        self.chars.next_if(|&(_, ch)| ch == '\r');
+                    }
+                    Some((_, '\r')) => {
+                        self.chars.next_if(|&(_, ch)| ch == '\n');
+                    }
+                    Some((_, ch)) => value.push(ch),
+                    None => return Err(self.error("unterminated property value")),
+                },
+                Some((_, ch)) => value.push(ch),
+                None => return Err(self.error("unterminated property value")),
+            }
+        }
+        self.skip_whitespace();
+        Ok(value)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, ch)| ch)
+    }
+
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.len, |&(offset, _)| offset)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, ch)| ch.is_whitespace()).is_some() {}
+    }
+
+    /// Consume `expected`, without skipping the whitespace after it.
+    fn expect_raw(&mut self, expected: char) -> Result<(), SgfError> {
+        match self.peek() {
+            Some(ch) if ch == expected => {
+                self.chars.next();
+                Ok(())
+            }
+            Some(ch) => Err(self.error(&format!("expected '{expected}' but found '{ch}'"))),
+            None => Err(self.error(&format!("expected '{expected}' but the record ended"))),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SgfError> {
+        self.expect_raw(expected)?;
+        self.skip_whitespace();
+        Ok(())
+    }
+
+    fn error(&mut self, reason: &str) -> SgfError {
+        SgfError::Syntax {
+            offset: self.offset(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_variations() {
+        let tree = parse("(;FF[4]SZ[9]\n;B[ee];W[]\n(;B[aa];W[bb])(;B[cc]C[why?]))").unwrap();
+        let line = tree.main_line();
+        assert_eq!(line.len(), 5);
+        assert_eq!(tree.node(line[0]).get("SZ").unwrap(), ["9"]);
+        assert_eq!(tree.node(line[2]).get("W").unwrap(), [""]);
+        assert_eq!(tree.node(line[3]).get("B").unwrap(), ["aa"]);
+        let variation = tree.children(line[2])[1];
+        assert_eq!(tree.node(variation).comment(), Some("why?"));
+    }
+
+    #[test]
+    fn round_trips() {
+        let sgf = "(;FF[4]C[root \\] comment]\n;B[ee]LB[ee:1](;W[cc];B[dd]TR[aa:bb])(;W[gg]N[other](;B[aa])(;B[bb])))\n";
+        let tree = parse(sgf).unwrap();
+        assert_eq!(tree.to_string(), sgf);
+        assert_eq!(parse(&tree.to_string()).unwrap(), tree);
+    }
+
+    #[test]
+    fn unescapes_values() {
+        let tree = parse("(;C[a \\] b\\\\ c\\\nd] AB [aa] [bb])").unwrap();
+        let root = tree.node(GameTree::ROOT);
+        assert_eq!(root.get("C").unwrap(), ["a ] b\\ cd"]);
+        assert_eq!(root.get("AB").unwrap(), ["aa", "bb"]);
+    }
+
+    #[test]
+    fn old_style_identifiers() {
+        let tree = parse("(;AddBlack[aa])").unwrap();
+        assert_eq!(tree.node(GameTree::ROOT).get("AB").unwrap(), ["aa"]);
+    }
+
+    #[test]
+    fn malformed_records() {
+        assert_eq!(
+            parse(";B[aa]").unwrap_err(),
+            SgfError::Syntax {
+                offset: 0,
+                reason: "expected '(' but found ';'".to_string()
+            }
+        );
+        assert!(parse("(;B[aa]").is_err());
+        assert!(parse("(;B[aa)").is_err());
+        assert!(parse("(;B)").is_err());
+        assert!(parse("()").is_err());
+    }
+}
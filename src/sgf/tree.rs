@@ -0,0 +1,151 @@
+//! The tree of moves and variations held by an SGF record.
+use crate::sgf::Node;
+use std::fmt::Display;
+
+/// Identifies a node of a `GameTree`.
+pub type NodeId = usize;
+
+/// A game record with every variation, each node holding its properties
+/// (moves, setup stones, comments and markup) along with its place in the tree.
+///
+/// The first child of a node continues the main line, and any later
+/// children are variations of it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameTree {
+    nodes: Vec<TreeNode>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TreeNode {
+    node: Node,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl GameTree {
+    /// The id of the root node, which holds the properties of the whole game.
+    pub const ROOT: NodeId = 0;
+
+    /// A tree holding only the root node.
+    pub fn new(root: Node) -> Self {
+        GameTree {
+            nodes: vec![TreeNode {
+                node: root,
+                parent: None,
+                children: Vec::new(),
+            }],
+        }
+    }
+
+    /// The properties of node `id`.
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id].node
+    }
+
+    /// The properties of node `id`, to change them.
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id].node
+    }
+
+    /// The node `id` follows, `None` for the root.
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    /// The nodes following `id`, the main line first.
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id].children
+    }
+
+    /// Add `node` after `parent`, as its last variation.
+    pub fn add_child(&mut self, parent: NodeId, node: Node) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(TreeNode {
+            node,
+            parent: Some(parent),
+            children: Vec::new(),
+        });
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    /// The nodes from the root to `id`, both included.
+    pub fn path_to(&self, id: NodeId) -> Vec<NodeId> {
+        let mut path = vec![id];
+        while let Some(parent) = self.parent(*path.last().unwrap()) {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+
+    /// The nodes of the main line, following the first child from the root.
+    pub fn main_line(&self) -> Vec<NodeId> {
+        let mut line = vec![Self::ROOT];
+        while let Some(&child) = self.children(*line.last().unwrap()).first() {
+            line.push(child);
+        }
+        line
+    }
+
+    fn write(&self, id: NodeId, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("(")?;
+        let mut id = id;
+        loop {
+            write!(f, "{}", self.node(id))?;
+            if id == Self::ROOT {
+                f.write_str("\n")?;
+            }
+            match self.children(id) {
+                [] => break,
+                [child] => id = *child,
+                variations => {
+                    for &variation in variations {
+                        self.write(variation, f)?;
+                    }
+                    break;
+                }
+            }
+        }
+        f.write_str(")")
+    }
+}
+
+impl Default for GameTree {
+    fn default() -> Self {
+        Self::new(Node::default())
+    }
+}
+
+impl Display for GameTree {
+    /// Writes the tree as an SGF record, with the root node on its own line.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write(Self::ROOT, f)?;
+        f.write_str("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(ident: &str, value: &str) -> Node {
+        let mut node = Node::default();
+        node.push(ident, vec![value.to_string()]);
+        node
+    }
+
+    #[test]
+    fn writes_variations() {
+        let mut tree = GameTree::new(node("SZ", "9"));
+        let first = tree.add_child(GameTree::ROOT, node("B", "ee"));
+        let main = tree.add_child(first, node("W", "cc"));
+        tree.add_child(main, node("B", "dd"));
+        tree.add_child(first, node("W", "gg"));
+        assert_eq!(tree.to_string(), "(;SZ[9]\n;B[ee](;W[cc];B[dd])(;W[gg]))\n");
+        assert_eq!(tree.main_line(), vec![0, 1, 2, 3]);
+        assert_eq!(tree.path_to(4), vec![0, 1, 4]);
+        assert_eq!(tree.children(first), &[2, 4]);
+        assert_eq!(tree.parent(4), Some(first));
+    }
+}
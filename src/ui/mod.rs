@@ -6,89 +6,312 @@ mod text_ui;
 pub use raw_mode_ui::RawModeUi;
 pub use text_ui::{StdTextUi, TextUi};
 
-use crate::game_logic::board::Board;
-use anyhow::{bail, Result};
+use crate::game_logic::board::{Board, Point};
+use crate::game_logic::error::MoveError;
+use crate::game_logic::game_move::Play;
+use crate::game_logic::notation::Notation;
+use crate::game_logic::overlay::{Mark, Overlay};
+use crate::game_logic::phase::GamePhase;
+use crate::game_logic::position::Position;
+use crate::game_logic::prisoners::Prisoners;
+use crate::game_logic::scoring::Score;
+use crate::game_logic::stone::Stone;
+use anyhow::Result;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum UserAction {
-    Move(usize, usize),
+    Move(Point),
+    /// Play no stone this turn. Two passes in a row end the game.
+    Pass,
+    /// Concede the game to the opponent.
+    Resign,
+    /// Take back the last move.
+    Undo,
+    /// Play the last undone move again.
+    Redo,
+    /// Mark the chain at this point dead, or alive again, once both
+    /// players have passed.
+    ToggleDead(Point),
+    /// Agree with the dead stones as they are marked.
+    Accept,
+    /// Abandon the game without a result.
     Quit,
     Noop,
 }
 
+/// Something that happened in the game, for UIs that keep track of it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    /// `stone` is asked for input next, in the given phase of the game.
+    ToMove { stone: Stone, phase: GamePhase },
+    /// A turn was played.
+    Played(Play),
+    /// A turn was taken back.
+    Undone(Play),
+}
+
+/// What a UI is shown of the game: a read-only snapshot of the board and
+/// everything around it that the players want to see.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameView {
+    /// The board, without the stones marked dead while scoring
+    pub board: Board,
+    /// The stones each player has captured, dead stones included
+    pub prisoners: Prisoners,
+    /// The color asked for input next
+    pub to_move: Stone,
+    /// The number of turns played so far, passes included
+    pub move_number: usize,
+    /// The turn played last
+    pub last_move: Option<Play>,
+    /// The point the player to move may not retake a ko at
+    pub ko: Option<Point>,
+    pub phase: GamePhase,
+    pub komi: f32,
+    /// Annotations to draw over the board, like territory, dead stones or
+    /// SGF markup
+    pub annotations: Overlay,
+}
+
+impl GameView {
+    /// A view of `position` being played, with the komi of its ruleset.
+    /// The last move is not known from a position alone.
+    pub fn from_position(position: &Position) -> Self {
+        GameView {
+            board: position.board().clone(),
+            prisoners: position.prisoners().clone(),
+            to_move: position.to_move(),
+            move_number: position.move_number(),
+            last_move: None,
+            ko: position.ko_point(),
+            phase: GamePhase::Playing,
+            komi: position.ruleset().default_komi(0),
+            annotations: Overlay::new(),
+        }
+    }
+
+    /// Everything to draw over the board: the last stone played, the ko,
+    /// then the annotations on top.
+    pub fn overlay(&self) -> Overlay {
+        let mut overlay = Overlay::new();
+        if let Some(Play::Stone(mv)) = self.last_move {
+            overlay.mark(mv.pos, Mark::LastMove);
+        }
+        if let Some(ko) = self.ko {
+            overlay.mark(ko, Mark::Ko);
+        }
+        overlay.extend(&self.annotations);
+        overlay
+    }
+}
+
 pub trait UserInterface {
-    /// User input, which will be passed to the controller
-    fn input(&mut self) -> Result<UserAction>;
+    /// User input, which will be passed to the controller. Points are read
+    /// against `board`, the game's board as it is now.
+    fn input(&mut self, board: &Board) -> Result<UserAction>;
+
+    /// View the game: the board, whose turn it is, the captures and so on
+    fn view(&mut self, game: &GameView) -> Result<()>;
+
+    /// Let the user know their last action was refused by the rules
+    fn show_error(&mut self, _err: &MoveError) -> Result<()> {
+        Ok(())
+    }
+
+    /// Show the final count once the game has been scored
+    fn show_score(&mut self, _score: &Score) -> Result<()> {
+        Ok(())
+    }
 
-    /// View the model
-    fn view(&mut self, board: &Board) -> Result<()>;
+    /// Hear about turns played or taken back, and whose turn it is
+    fn notify(&mut self, _event: &GameEvent) -> Result<()> {
+        Ok(())
+    }
 }
 
-fn parse_move_position(mv: &str) -> Result<(usize, usize)> {
-    let mv = mv.trim();
-    if mv.len() != 2 {
-        bail!("Move should be 2 characters");
+/// Chooses the moves of one color in a game. A player is told what happens
+/// in the game like a UI is, but only asked for input on its own turns.
+///
+/// Every `UserInterface` is a player, asking its user for the moves, and so
+/// are computer opponents like `gtp::GtpPlayer`.
+pub trait Player {
+    /// The player's next action on `board`, asked for when it is their turn
+    fn next_action(&mut self, board: &Board) -> Result<UserAction>;
+
+    /// See the game: the board, whose turn it is, the captures and so on
+    fn see_game(&mut self, _game: &GameView) -> Result<()> {
+        Ok(())
     }
-    let row = mv.as_bytes()[0].to_ascii_uppercase();
-    let col = mv.as_bytes()[1];
 
-    // Error if not alphabetic
-    if !row.is_ascii_alphabetic() {
-        bail!("Non-alphabetical row");
+    /// Hear that the player's last action was refused by the rules
+    fn action_refused(&mut self, _err: &MoveError) -> Result<()> {
+        Ok(())
     }
-    if !col.is_ascii_digit() {
-        bail!("Non-digit column");
+
+    /// See the final count once the game has been scored
+    fn see_score(&mut self, _score: &Score) -> Result<()> {
+        Ok(())
     }
 
-    let row = (row - b'A') as usize;
-    let col: usize = (col - b'0') as usize - 1;
-    Ok((row, col))
+    /// Hear about turns played or taken back, and whose turn it is
+    fn hear(&mut self, _event: &GameEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A UI plays by asking its user, and shows them everything it hears.
+impl<T: UserInterface> Player for T {
+    fn next_action(&mut self, board: &Board) -> Result<UserAction> {
+        self.input(board)
+    }
+
+    fn see_game(&mut self, game: &GameView) -> Result<()> {
+        self.view(game)
+    }
+
+    fn action_refused(&mut self, err: &MoveError) -> Result<()> {
+        self.show_error(err)
+    }
+
+    fn see_score(&mut self, score: &Score) -> Result<()> {
+        self.show_score(score)
+    }
+
+    fn hear(&mut self, event: &GameEvent) -> Result<()> {
+        self.notify(event)
+    }
+}
+
+/// Turn a line of user input into the action it asks for. Points are read
+/// in `notation` against `board`, and ones it does not have are refused.
+fn parse_action(inp: &str, board: &Board, notation: Notation) -> Result<UserAction> {
+    match inp.trim().to_ascii_lowercase().as_str() {
+        "q" | "quit" => Ok(UserAction::Quit),
+        "pass" => Ok(UserAction::Pass),
+        "resign" => Ok(UserAction::Resign),
+        "accept" => Ok(UserAction::Accept),
+        "undo" => Ok(UserAction::Undo),
+        "redo" => Ok(UserAction::Redo),
+        mv if mv.starts_with("mark ") => {
+            let point = notation.parse(&mv["mark ".len()..], board)?;
+            Ok(UserAction::ToggleDead(point))
+        }
+        mv => Ok(UserAction::Move(notation.parse(mv, board)?)),
+    }
+}
+
+/// The lines of text describing the game around the board: whose turn it
+/// is, the last move and any ko, and the captures and komi.
+fn status_lines(game: &GameView, notation: Notation) -> [String; 3] {
+    let color = |stone| match stone {
+        Stone::Black => "Black",
+        Stone::White => "White",
+        Stone::Empty => "Nobody",
+    };
+    let turn = match game.phase {
+        GamePhase::Playing => format!(
+            "Move {}: {} to play",
+            game.move_number + 1,
+            color(game.to_move)
+        ),
+        GamePhase::Scoring => format!(
+            "Scoring: {} marks dead stones or accepts",
+            color(game.to_move)
+        ),
+        GamePhase::Finished => "The game is over".to_string(),
+    };
+    let mut last = match game.last_move {
+        Some(Play::Stone(mv)) => format!(
+            "Last move: {} {}",
+            color(mv.stone),
+            notation.format(mv.pos, game.board.height)
+        ),
+        Some(Play::Pass { stone, .. }) => format!("Last move: {} passed", color(stone)),
+        None => String::new(),
+    };
+    if let Some(ko) = game.ko {
+        let ko = format!("Ko at {}", notation.format(ko, game.board.height));
+        last = if last.is_empty() {
+            ko
+        } else {
+            format!("{last}  {ko}")
+        };
+    }
+    let captures = format!("{}  Komi: {}", game.prisoners, game.komi);
+    [turn, last, captures]
+}
+
+/// The board drawn with legends in `notation`.
+fn draw_board(board: &Board, notation: Notation, overlay: &Overlay) -> String {
+    board
+        .to_ascii(notation, overlay)
+        .iter()
+        .map(ToString::to_string)
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    // parse_move tests
-    #[test]
-    fn parse_move_works_uppper_case() {
-        let res = parse_move_position("A1").unwrap();
-        assert_eq!((0, 0), res);
-    }
-
-    #[test]
-    fn parse_move_works_lower_case() {
-        let res = parse_move_position("a1").unwrap();
-        assert_eq!((0, 0), res);
+    fn board() -> Board {
+        Board::new(9, 9)
     }
 
-    #[test]
-    fn parse_move_solo_alpha_should_error() {
-        parse_move_position("a").unwrap_err();
+    fn at(row: usize, col: usize) -> Point {
+        board().point(row, col).unwrap()
     }
 
-    #[test]
-    fn parse_move_solo_digit_should_error() {
-        parse_move_position("1").unwrap_err();
+    fn parse(inp: &str) -> Result<UserAction> {
+        parse_action(inp, &board(), Notation::RowLetter)
     }
 
+    // parse_action tests
     #[test]
-    fn parse_move_empty_should_error() {
-        parse_move_position("").unwrap_err();
+    fn parse_action_pass_and_resign() {
+        assert_eq!(UserAction::Pass, parse("pass\n").unwrap());
+        assert_eq!(UserAction::Resign, parse("Resign").unwrap());
+        assert_eq!(UserAction::Quit, parse("q").unwrap());
+        assert_eq!(UserAction::Move(at(1, 2)), parse("b3").unwrap());
+        assert_eq!(UserAction::ToggleDead(at(1, 2)), parse("mark b3").unwrap());
+        assert_eq!(UserAction::Accept, parse("accept").unwrap());
+        assert_eq!(UserAction::Undo, parse("undo").unwrap());
+        assert_eq!(UserAction::Redo, parse("redo").unwrap());
+        parse("passs").unwrap_err();
     }
 
     #[test]
-    fn parse_move_all_digits_should_error() {
-        parse_move_position("11").unwrap_err();
+    fn parse_move_off_the_board_should_error() {
+        for mv in ["j1", "z9", "mark j9"] {
+            let err = parse(mv).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<MoveError>(),
+                Some(&MoveError::OutOfBounds),
+                "{mv}"
+            );
+        }
+        assert_eq!(UserAction::Move(at(8, 8)), parse("i9").unwrap());
     }
 
     #[test]
-    fn parse_move_all_alpha_should_error() {
-        parse_move_position("aa").unwrap_err();
+    fn parse_points_in_gtp_notation() {
+        let parse = |inp| parse_action(inp, &board(), Notation::Gtp).unwrap();
+        assert_eq!(UserAction::Move(at(5, 3)), parse("d4"));
+        assert_eq!(UserAction::ToggleDead(at(0, 8)), parse("mark J9"));
+        assert_eq!(UserAction::Pass, parse("pass"));
     }
 
     #[test]
-    fn parse_move_extra_char_should_error() {
-        parse_move_position("a1b").unwrap_err();
+    fn parse_errors_say_what_was_wrong() {
+        let err = parse("a0").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MoveError>(),
+            Some(&MoveError::ParseError {
+                input: "a0".to_string(),
+                reason: "Columns are numbered from 1".to_string()
+            })
+        );
+        let err = parse("mark 3c").unwrap_err();
+        assert_eq!(err.to_string(), "'3c': Non-alphabetical row");
     }
 }
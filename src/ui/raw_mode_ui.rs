@@ -1,21 +1,193 @@
 #![allow(unused)]
 use super::*;
 
-use crate::game_logic::board::Board;
+use crate::game_logic::board::{Board, Layout};
+use crate::game_logic::overlay::{star_points, Overlay};
 use crate::game_logic::stone::Icon;
 
 use std::io::*;
 
 use anyhow::{Context, Error, Result};
 use colored::Colorize;
-use crossterm::{event::*, style::*, terminal::*, *};
+use crossterm::{cursor::*, event::*, style::*, terminal::*, *};
 
-pub struct RawModeUi;
+/// The keys, shown below the board.
+const HELP: &str = "arrows/hjkl move, enter/space/click play, p pass, u undo, r redo, \
+                    a accept, : type a command, q quit";
+
+/// The lines below the board: the game's status, then the last error, the
+/// keys, and the line commands are typed on.
+const ERROR_LINE: u16 = 3;
+const HELP_LINE: u16 = 4;
+const PROMPT_LINE: u16 = 5;
+
+/// An interactive terminal UI. A highlighted cursor is moved over the board
+/// with the arrow keys or hjkl, and single keys play, pass and so on.
+/// Points can be clicked too, and a ghost stone follows the mouse.
+/// Anything else, like resigning or naming a point, can be typed after `:`.
+pub struct RawModeUi {
+    /// Error from the last action, shown below the board on the next redraw
+    last_error: Option<MoveError>,
+    /// The board last viewed, which is on the screen
+    board: Board,
+    /// The marks drawn over the board last viewed
+    overlay: Overlay,
+    /// The star points of the board last viewed
+    stars: Vec<(usize, usize)>,
+    /// How points are named, in input and on the board
+    notation: Notation,
+    /// The point the cursor is on, as `(row, col)`
+    cursor: (usize, usize),
+    /// The phase the game is in, which decides what choosing a point does
+    phase: GamePhase,
+    /// The color to move, which the ghost stone shows
+    to_move: Stone,
+    /// The empty point the mouse is over, where the ghost stone is
+    hover: Option<(usize, usize)>,
+    /// Set once the terminal has been put in raw mode, on the first input
+    raw: bool,
+}
 
 impl RawModeUi {
     pub fn new() -> Self {
-        execute!(stdout(), EnterAlternateScreen, Clear(ClearType::All));
-        Self
+        execute!(stdout(), EnterAlternateScreen, Clear(ClearType::All), Hide);
+        Self {
+            last_error: None,
+            board: Board::new(0, 0),
+            overlay: Overlay::new(),
+            stars: Vec::new(),
+            notation: Notation::default(),
+            cursor: (0, 0),
+            phase: GamePhase::Playing,
+            to_move: Stone::Black,
+            hover: None,
+            raw: false,
+        }
+    }
+
+    /// Name points in `notation` instead of the default one.
+    pub fn with_notation(mut self, notation: Notation) -> Self {
+        self.notation = notation;
+        self
+    }
+
+    /// Draw the point at `(row, col)` where it is on the screen, with its
+    /// mark, highlighted if the cursor is on it, with a faint stone if the
+    /// mouse is while stones are being played.
+    fn queue_point(&self, out: &mut impl Write, (row, col): (usize, usize)) -> Result<()> {
+        let Ok(point) = self.board.point(row, col) else {
+            return Ok(());
+        };
+        let stone = self.board.state[row][col];
+        let (x, y) = Layout::new(&self.board, self.notation).screen_pos((row, col));
+        queue!(out, MoveTo(x as u16, y as u16))?;
+        if (row, col) == self.cursor {
+            queue!(out, SetAttribute(Attribute::Reverse))?;
+        }
+        let icon = match stone {
+            Stone::Empty if self.hover == Some((row, col)) && self.phase == GamePhase::Playing => {
+                queue!(out, SetAttribute(Attribute::Dim))?;
+                self.to_move.get_icon()
+            }
+            _ => self.board.icon_at(point, &self.overlay, &self.stars),
+        };
+        queue!(out, Print(icon), SetAttribute(Attribute::Reset))?;
+        Ok(())
+    }
+
+    /// Show the ghost stone at the empty point under the mouse at `(x, y)`,
+    /// if there is one, and take it away from where it was.
+    fn hover(&mut self, (x, y): (u16, u16)) -> Result<()> {
+        let layout = Layout::new(&self.board, self.notation);
+        let hover = layout
+            .point_at((x as usize, y as usize))
+            .filter(|&(row, col)| self.board.state[row][col] == Stone::Empty);
+        if hover == self.hover {
+            return Ok(());
+        }
+        let before = std::mem::replace(&mut self.hover, hover);
+        let mut out = stdout();
+        for point in [before, hover].into_iter().flatten() {
+            self.queue_point(&mut out, point)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Move the cursor by `rows` and `cols`, staying on the board, and
+    /// redraw the two points it moved between.
+    fn move_cursor(&mut self, rows: isize, cols: isize) -> Result<()> {
+        let before = self.cursor;
+        self.cursor = step(before, (rows, cols), &self.board);
+        let mut out = stdout();
+        self.queue_point(&mut out, before)?;
+        self.queue_point(&mut out, self.cursor)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// What a mouse event asks for. Clicking a point moves the cursor there
+    /// and chooses it, and moving the mouse moves the ghost stone.
+    fn mouse_command(&mut self, mouse: MouseEvent) -> Result<KeyCommand> {
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let layout = Layout::new(&self.board, self.notation);
+                let Some(point) = layout.point_at((mouse.column as usize, mouse.row as usize))
+                else {
+                    return Ok(KeyCommand::Ignore);
+                };
+                let before = std::mem::replace(&mut self.cursor, point);
+                let mut out = stdout();
+                self.queue_point(&mut out, before)?;
+                self.queue_point(&mut out, point)?;
+                out.flush()?;
+                Ok(KeyCommand::Choose)
+            }
+            MouseEventKind::Moved => {
+                self.hover((mouse.column, mouse.row))?;
+                Ok(KeyCommand::Ignore)
+            }
+            _ => Ok(KeyCommand::Ignore),
+        }
+    }
+
+    /// Show `text` on line `line` below the board, in place of what was there.
+    fn queue_status(&self, out: &mut impl Write, line: u16, text: &str) -> Result<()> {
+        let top = Layout::new(&self.board, self.notation).lines() as u16;
+        queue!(
+            out,
+            MoveTo(0, top + line),
+            Clear(ClearType::CurrentLine),
+            Print(text)
+        )?;
+        Ok(())
+    }
+
+    /// Read a line typed after `:`, shown on the prompt line. `None` if it
+    /// was given up on with Esc.
+    fn read_command(&mut self) -> Result<Option<String>> {
+        let mut command = String::new();
+        loop {
+            let mut out = stdout();
+            self.queue_status(&mut out, PROMPT_LINE, &format!(":{command}"))?;
+            out.flush()?;
+            let Some(key) = next_key()? else {
+                return Ok(None);
+            };
+            match key.code {
+                KeyCode::Enter => return Ok(Some(command)),
+                KeyCode::Esc => break,
+                KeyCode::Backspace => {
+                    command.pop();
+                }
+                KeyCode::Char(c) => command.push(c),
+                _ => {}
+            }
+        }
+        let mut out = stdout();
+        self.queue_status(&mut out, PROMPT_LINE, "")?;
+        out.flush()?;
+        Ok(None)
     }
 }
 
@@ -27,32 +199,249 @@ impl Default for RawModeUi {
 
 impl Drop for RawModeUi {
     fn drop(&mut self) {
-        disable_raw_mode().unwrap();
-        execute!(stdout(), LeaveAlternateScreen);
+        if self.raw {
+            execute!(stdout(), DisableMouseCapture);
+            disable_raw_mode().unwrap();
+        }
+        execute!(stdout(), Show, LeaveAlternateScreen);
     }
 }
 
 impl UserInterface for RawModeUi {
-    fn input(&mut self) -> Result<UserAction> {
-        write!(stdout(), "Enter a move, or quit (q)")?;
-        stdout().flush()?;
+    fn input(&mut self, board: &Board) -> Result<UserAction> {
+        if !self.raw {
+            enable_raw_mode().with_context(|| "Failed to read keys from the terminal")?;
+            execute!(stdout(), EnableMouseCapture)?;
+            self.raw = true;
+        }
+        loop {
+            let Some(event) = next_event()? else {
+                // the input has ended, so there will be no more moves
+                return Ok(UserAction::Quit);
+            };
+            let command = match event {
+                Event::Key(key) => key_command(key),
+                Event::Mouse(mouse) => self.mouse_command(mouse)?,
+                _ => KeyCommand::Ignore,
+            };
+            match command {
+                KeyCommand::Act(action) => return Ok(action),
+                KeyCommand::Choose => {
+                    let point = board.point(self.cursor.0, self.cursor.1)?;
+                    return Ok(match self.phase {
+                        GamePhase::Scoring => UserAction::ToggleDead(point),
+                        _ => UserAction::Move(point),
+                    });
+                }
+                KeyCommand::Cursor(rows, cols) => self.move_cursor(rows, cols)?,
+                KeyCommand::Type => {
+                    if let Some(command) = self.read_command()? {
+                        return parse_action(&command, board, self.notation);
+                    }
+                }
+                KeyCommand::Ignore => {}
+            }
+        }
+    }
 
-        let mut inp = String::new();
-        stdin()
-            .read_line(&mut inp)
-            .with_context(|| "Failed to read input")?;
+    /// Draws the board on the first view and whenever its size changes.
+    /// Otherwise only the points whose stones or marks changed are drawn
+    /// again.
+    fn view(&mut self, game: &GameView) -> Result<()> {
+        let board = &game.board;
+        let mut out = stdout();
+        let resized = (board.width, board.height) != (self.board.width, self.board.height);
+        let before = std::mem::replace(&mut self.board, board.clone());
+        let marks_before = std::mem::replace(&mut self.overlay, game.overlay());
+        // a stone played where the ghost stone was replaces it
+        self.hover = self
+            .hover
+            .filter(|&(row, col)| !resized && board.state[row][col] == Stone::Empty);
+        if resized {
+            self.cursor = (board.height / 2, board.width / 2);
+            self.stars = star_points(board.width, board.height);
+            queue!(out, Clear(ClearType::All))?;
+            for (y, line) in draw_board(board, self.notation, &self.overlay)
+                .lines()
+                .enumerate()
+            {
+                queue!(out, MoveTo(0, y as u16), Print(line))?;
+            }
+            self.queue_point(&mut out, self.cursor)?;
+        } else {
+            for (row, (old, new)) in before.state.iter().zip(&board.state).enumerate() {
+                for col in 0..new.len() {
+                    let point = board.point(row, col)?;
+                    if old[col] != new[col] || marks_before.get(point) != self.overlay.get(point) {
+                        self.queue_point(&mut out, (row, col))?;
+                    }
+                }
+            }
+        }
+        for (line, text) in status_lines(game, self.notation).iter().enumerate() {
+            self.queue_status(&mut out, line as u16, text)?;
+        }
+        let error = match self.last_error.take() {
+            Some(err) => err.to_string().red().to_string(),
+            None => String::new(),
+        };
+        self.queue_status(&mut out, ERROR_LINE, &error)?;
+        self.queue_status(&mut out, HELP_LINE, HELP)?;
+        self.queue_status(&mut out, PROMPT_LINE, "")?;
+        out.flush()?;
+        Ok(())
+    }
+
+    fn show_error(&mut self, err: &MoveError) -> Result<()> {
+        self.last_error = Some(err.clone());
+        Ok(())
+    }
 
-        if inp.trim() == "q" {
-            return Ok(UserAction::Quit);
+    fn show_score(&mut self, score: &Score) -> Result<()> {
+        let mut out = stdout();
+        for (line, text) in score.to_string().lines().enumerate() {
+            self.queue_status(&mut out, line as u16, text)?;
+        }
+        let lines = score.to_string().lines().count() as u16;
+        self.queue_status(&mut out, lines, "Press any key to leave the game")?;
+        out.flush()?;
+        // keep the alternate screen up until the players have seen the count
+        if self.raw {
+            next_key()?;
+        } else {
+            stdin()
+                .read_line(&mut String::new())
+                .with_context(|| "Failed to read input")?;
         }
-        let mv = parse_move_position(&inp)?;
-        Ok(UserAction::Move(mv.0, mv.1))
+        Ok(())
     }
 
-    fn view(&mut self, board: &Board) -> Result<()> {
-        queue!(stdout(), Clear(ClearType::All));
-        writeln!(stdout(), "{board}")?;
-        stdout().flush()?;
+    fn notify(&mut self, event: &GameEvent) -> Result<()> {
+        if let GameEvent::ToMove { stone, phase } = event {
+            self.to_move = *stone;
+            let before = std::mem::replace(&mut self.phase, *phase);
+            // the ghost stone comes and goes with the playing phase
+            if let Some(hover) = self.hover.filter(|_| before != *phase) {
+                let mut out = stdout();
+                self.queue_point(&mut out, hover)?;
+                out.flush()?;
+            }
+        }
         Ok(())
     }
 }
+
+/// What a key press asks for.
+#[derive(Debug, PartialEq, Eq)]
+enum KeyCommand {
+    /// An action that needs no point
+    Act(UserAction),
+    /// Play, or mark dead, the point under the cursor
+    Choose,
+    /// Move the cursor by this many rows and columns
+    Cursor(isize, isize),
+    /// Type a command, like one for the text UI
+    Type,
+    Ignore,
+}
+
+fn key_command(key: KeyEvent) -> KeyCommand {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        return match key.code {
+            KeyCode::Char('c') => KeyCommand::Act(UserAction::Quit),
+            _ => KeyCommand::Ignore,
+        };
+    }
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => KeyCommand::Cursor(-1, 0),
+        KeyCode::Down | KeyCode::Char('j') => KeyCommand::Cursor(1, 0),
+        KeyCode::Left | KeyCode::Char('h') => KeyCommand::Cursor(0, -1),
+        KeyCode::Right | KeyCode::Char('l') => KeyCommand::Cursor(0, 1),
+        KeyCode::Enter | KeyCode::Char(' ') => KeyCommand::Choose,
+        KeyCode::Char('p') => KeyCommand::Act(UserAction::Pass),
+        KeyCode::Char('u') => KeyCommand::Act(UserAction::Undo),
+        KeyCode::Char('r') => KeyCommand::Act(UserAction::Redo),
+        KeyCode::Char('a') => KeyCommand::Act(UserAction::Accept),
+        KeyCode::Char('q') | KeyCode::Esc => KeyCommand::Act(UserAction::Quit),
+        KeyCode::Char(':') => KeyCommand::Type,
+        _ => KeyCommand::Ignore,
+    }
+}
+
+/// The next key pressed or mouse event, or `None` if the terminal has
+/// gone away.
+fn next_event() -> Result<Option<Event>> {
+    loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Release => {}
+            Ok(event) => return Ok(Some(event)),
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err).with_context(|| "Failed to read input"),
+        }
+    }
+}
+
+/// The next key pressed, or `None` if the terminal has gone away.
+fn next_key() -> Result<Option<KeyEvent>> {
+    while let Some(event) = next_event()? {
+        if let Event::Key(key) = event {
+            return Ok(Some(key));
+        }
+    }
+    Ok(None)
+}
+
+/// The point `by` rows and columns away from `from`, stopping at the edges
+/// of `board`.
+fn step(from: (usize, usize), by: (isize, isize), board: &Board) -> (usize, usize) {
+    let clamp =
+        |at: usize, by: isize, len: usize| at.saturating_add_signed(by).min(len.saturating_sub(1));
+    (
+        clamp(from.0, by.0, board.height),
+        clamp(from.1, by.1, board.width),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn keys_ask_for_actions() {
+        assert_eq!(key_command(key(KeyCode::Up)), KeyCommand::Cursor(-1, 0));
+        assert_eq!(
+            key_command(key(KeyCode::Char('l'))),
+            KeyCommand::Cursor(0, 1)
+        );
+        assert_eq!(key_command(key(KeyCode::Char(' '))), KeyCommand::Choose);
+        assert_eq!(key_command(key(KeyCode::Enter)), KeyCommand::Choose);
+        assert_eq!(
+            key_command(key(KeyCode::Char('p'))),
+            KeyCommand::Act(UserAction::Pass)
+        );
+        assert_eq!(
+            key_command(key(KeyCode::Char('u'))),
+            KeyCommand::Act(UserAction::Undo)
+        );
+        assert_eq!(
+            key_command(key(KeyCode::Char('q'))),
+            KeyCommand::Act(UserAction::Quit)
+        );
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(key_command(ctrl_c), KeyCommand::Act(UserAction::Quit));
+        assert_eq!(key_command(key(KeyCode::Char(':'))), KeyCommand::Type);
+        assert_eq!(key_command(key(KeyCode::Char('x'))), KeyCommand::Ignore);
+    }
+
+    #[test]
+    fn cursor_stays_on_board() {
+        let board = Board::new(9, 9);
+        assert_eq!(step((0, 0), (-1, 0), &board), (0, 0));
+        assert_eq!(step((0, 0), (0, 1), &board), (0, 1));
+        assert_eq!(step((8, 8), (1, 1), &board), (8, 8));
+    }
+}
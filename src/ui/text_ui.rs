@@ -23,11 +23,17 @@ impl Default for StdTextUi {
 }
 
 impl UserInterface for StdTextUi {
-    fn view(&mut self, board: &Board) -> Result<()> {
-        self.ui.view(board)
+    fn view(&mut self, game: &GameView) -> Result<()> {
+        self.ui.view(game)
     }
-    fn input(&mut self) -> Result<UserAction> {
-        self.ui.input()
+    fn input(&mut self, board: &Board) -> Result<UserAction> {
+        self.ui.input(board)
+    }
+    fn show_error(&mut self, err: &MoveError) -> Result<()> {
+        self.ui.show_error(err)
+    }
+    fn show_score(&mut self, score: &Score) -> Result<()> {
+        self.ui.show_score(score)
     }
 }
 
@@ -35,6 +41,8 @@ impl UserInterface for StdTextUi {
 pub struct TextUi<R: Read, W: Write> {
     reader: std::io::BufReader<R>,
     writer: std::io::BufWriter<W>,
+    /// How points are named, in input and on the board
+    notation: Notation,
 }
 
 impl<R: Read, W: Write> TextUi<R, W> {
@@ -42,48 +50,156 @@ impl<R: Read, W: Write> TextUi<R, W> {
         Self {
             reader: BufReader::new(reader),
             writer: BufWriter::new(writer),
+            notation: Notation::default(),
         }
     }
+
+    /// Name points in `notation` instead of the default one.
+    pub fn with_notation(mut self, notation: Notation) -> Self {
+        self.notation = notation;
+        self
+    }
 }
 
 impl<R: Read, W: Write> UserInterface for TextUi<R, W> {
-    fn input(&mut self) -> Result<UserAction> {
-        write!(self.writer, "Enter a move, or quit (q)")?;
+    fn input(&mut self, board: &Board) -> Result<UserAction> {
+        write!(
+            self.writer,
+            "Enter a move, pass, undo, redo, resign, or quit (q). Once both pass: mark <pos> or accept"
+        )?;
         self.writer.flush()?;
 
         let mut inp = String::new();
-        self.reader
+        let read = self
+            .reader
             .read_line(&mut inp)
             .with_context(|| "Failed to read input")?;
-
-        if inp.trim() == "q" {
+        if read == 0 {
+            // the input has ended, so there will be no more moves
             return Ok(UserAction::Quit);
         }
-        let mv = parse_move_position(&inp)?;
-        Ok(UserAction::Move(mv.0, mv.1))
+
+        parse_action(&inp, board, self.notation)
+    }
+
+    fn view(&mut self, game: &GameView) -> Result<()> {
+        let drawn = draw_board(&game.board, self.notation, &game.overlay());
+        writeln!(self.writer, "{drawn}").with_context(|| "Failed to prompt user")?;
+        for line in status_lines(game, self.notation) {
+            if !line.is_empty() {
+                writeln!(self.writer, "{line}").with_context(|| "Failed to prompt user")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn show_error(&mut self, err: &MoveError) -> Result<()> {
+        writeln!(self.writer, "{}", err).with_context(|| "Failed to report error")
     }
 
-    fn view(&mut self, board: &Board) -> Result<()> {
-        writeln!(self.writer, "{}", board).with_context(|| "Failed to prompt user")
+    fn show_score(&mut self, score: &Score) -> Result<()> {
+        writeln!(self.writer, "{}", score).with_context(|| "Failed to show score")?;
+        self.writer.flush().with_context(|| "Failed to show score")
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn view(width: usize, height: usize) -> GameView {
+        GameView::from_position(&Position::new(width, height))
+    }
+
     #[test]
     fn get_move() {
-        let reader = std::io::Cursor::new(String::from("a1\n"));
+        let reader = std::io::Cursor::new(String::from("a1\nd1\n"));
+        let mut ui = TextUi::new(reader, vec![]);
+        // moves are read against the board given, even before any view
+        let board = Board::new(3, 3);
+        let action = ui.input(&board).unwrap();
+        assert_eq!(UserAction::Move(board.point(0, 0).unwrap()), action);
+        // d is past the last row of the board
+        let err = ui.input(&board).unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&MoveError::OutOfBounds));
+    }
+
+    #[test]
+    fn view_shows_captures() {
+        let reader = std::io::Cursor::new(String::new());
+        let mut ui = TextUi::new(reader, vec![]);
+        ui.view(&view(3, 3)).unwrap();
+        let output = String::from_utf8(ui.writer.into_inner().unwrap()).unwrap();
+        assert!(output.contains("Captures - Black: 0  White: 0  Komi: 6.5"));
+    }
+
+    #[test]
+    fn view_shows_turn_last_move_and_ko() {
+        let mut position = Position::new(4, 4);
+        for (row, col) in [
+            (0, 1),
+            (0, 2),
+            (1, 0),
+            (1, 3),
+            (2, 1),
+            (2, 2),
+            (3, 3),
+            (1, 1),
+        ] {
+            let point = position.board().point(row, col).unwrap();
+            position.play(position.to_move(), point).unwrap();
+        }
+        let point = position.board().point(1, 2).unwrap();
+        let played = position.play(Stone::Black, point).unwrap();
+        let mut game = GameView::from_position(&position);
+        game.last_move = Some(played.play);
+        let mut ui = TextUi::new(std::io::Cursor::new(String::new()), vec![]);
+        ui.view(&game).unwrap();
+        let output = String::from_utf8(ui.writer.into_inner().unwrap()).unwrap();
+        assert!(output.contains("Move 10: White to play"));
+        assert!(output.contains("Last move: Black B3  Ko at B2"));
+        assert!(output.contains("Captures - Black: 1  White: 0"));
+        // the stone just played and the ko are marked on the board
+        assert!(output.contains('◉'));
+        assert!(output.contains('□'));
+    }
+
+    #[test]
+    fn two_digit_columns_and_gtp_points() {
+        let reader = std::io::Cursor::new(String::from("k12\nd4\n"));
         let mut ui = TextUi::new(reader, vec![]);
-        let action = ui.input().unwrap();
-        assert_eq!(UserAction::Move(0, 0), action);
+        let game = view(13, 13);
+        let board = &game.board;
+        ui.view(&game).unwrap();
+        assert_eq!(
+            UserAction::Move(board.point(10, 11).unwrap()),
+            ui.input(board).unwrap()
+        );
+        let mut ui = ui.with_notation(Notation::Gtp);
+        ui.view(&game).unwrap();
+        assert_eq!(
+            UserAction::Move(board.point(9, 3).unwrap()),
+            ui.input(board).unwrap()
+        );
+        let output = String::from_utf8(ui.writer.into_inner().unwrap()).unwrap();
+        assert!(output.contains("13┃"));
+        assert!(output.contains("A B C D E F G H J K L M N"));
     }
 
     #[test]
     fn get_quit() {
         let reader = std::io::Cursor::new(String::from("q\n"));
         let mut ui = TextUi::new(reader, vec![]);
-        let action = ui.input().unwrap();
+        let action = ui.input(&Board::new(3, 3)).unwrap();
         assert_eq!(UserAction::Quit, action);
     }
+
+    #[test]
+    fn end_of_input_quits() {
+        let reader = std::io::Cursor::new(String::from("a1\n"));
+        let mut ui = TextUi::new(reader, vec![]);
+        let board = Board::new(3, 3);
+        ui.input(&board).unwrap();
+        assert_eq!(UserAction::Quit, ui.input(&board).unwrap());
+    }
 }
@@ -1,6 +1,8 @@
 use go::game::Game;
 use go::*;
+use std::cell::Cell;
 use std::collections::VecDeque;
+use std::rc::Rc;
 
 struct MockUi {
     inputs: VecDeque<(usize, usize)>,
@@ -15,13 +17,13 @@ impl MockUi {
 }
 
 impl UserInterface for MockUi {
-    fn view(&mut self, _board: &Board) -> anyhow::Result<()> {
+    fn view(&mut self, _game: &GameView) -> anyhow::Result<()> {
         Ok(())
     }
 
-    fn input(&mut self) -> anyhow::Result<UserAction> {
+    fn input(&mut self, board: &Board) -> anyhow::Result<UserAction> {
         if let Some((row, col)) = self.inputs.pop_front() {
-            return Ok(UserAction::Move(row, col));
+            return Ok(UserAction::Move(board.point(row, col)?));
         }
 
         Ok(UserAction::Quit)
@@ -35,8 +37,176 @@ fn it_works() {
     let mut game = Game::new_game(3, 3, ui);
     game.start_game().unwrap();
 
-    let state = game.board.get_state();
+    let state = game.board().get_state();
     assert_eq!(state[0][0], Stone::Black);
     assert_eq!(state[1][1], Stone::White);
     assert_eq!(state[2][2], Stone::Black);
 }
+
+#[test]
+fn refused_move_does_not_end_game() {
+    // White's a1 is suicide, so White gets to play b2 instead
+    let ui = MockUi::new(vec![(0, 1), (2, 2), (1, 0), (0, 0), (1, 1)]);
+
+    let mut game = Game::new_game(3, 3, ui);
+    game.start_game().unwrap();
+
+    let state = game.board().get_state();
+    assert_eq!(state[0][0], Stone::Empty);
+    assert_eq!(state[1][1], Stone::White);
+}
+
+#[test]
+fn gtp_mode_answers_over_stdio() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_go"))
+        .arg("--gtp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    engine
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"1 boardsize 9\n2 play b E5\n3 genmove w\nquit\n")
+        .unwrap();
+    let output = engine.wait_with_output().unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "=1 \n\n=2 \n\n=3 D6\n\n= \n\n"
+    );
+}
+
+#[test]
+fn plays_against_a_gtp_engine() {
+    // the crate's own GTP mode stands in for an engine like GnuGo
+    let engine = gtp::GtpPlayer::spawn(Stone::White, env!("CARGO_BIN_EXE_go"), &["--gtp"]).unwrap();
+
+    let mut game =
+        Game::new_game(5, 5, MockUi::new(vec![(2, 2), (4, 4)])).with_player(Stone::White, engine);
+    game.start_game().unwrap();
+
+    let state = game.board().get_state();
+    assert_eq!(state[2][2], Stone::Black);
+    assert_eq!(state[4][4], Stone::Black);
+    // the engine's replies, the best points it found next to the edge
+    assert_eq!(state[1][1], Stone::White);
+    assert_eq!(state[1][2], Stone::White);
+    assert_eq!(game.moves().len(), 4);
+}
+
+#[test]
+fn gtp_engine_is_given_the_game_komi() {
+    // the commands are copied to a file on their way to the engine, up to
+    // `quit`, as the player waits for the engine with its stdin still open
+    let log = std::env::temp_dir().join(format!("go-komi-{}.gtp", std::process::id()));
+    let script = format!(
+        "while read -r line; do echo \"$line\" >> '{}'; echo \"$line\"; \
+         [ \"$line\" = quit ] && break; done | '{}' --gtp",
+        log.display(),
+        env!("CARGO_BIN_EXE_go")
+    );
+    let engine = gtp::GtpPlayer::spawn(Stone::White, "sh", &["-c", &script]).unwrap();
+
+    let mut game = Game::new_game(5, 5, MockUi::new(vec![(2, 2)]))
+        .with_komi(3.5)
+        .with_player(Stone::White, engine);
+    game.start_game().unwrap();
+    drop(game);
+
+    let commands = std::fs::read_to_string(&log).unwrap();
+    std::fs::remove_file(&log).unwrap();
+    assert!(commands.contains("\nkomi 3.5\n"), "{commands}");
+}
+
+#[test]
+fn plays_a_handicap_game_against_a_gtp_engine() {
+    let engine = |color| gtp::GtpPlayer::spawn(color, env!("CARGO_BIN_EXE_go"), &["--gtp"]);
+
+    // the stones are on the star points, so the engine is given a fixed handicap
+    let mut game = Game::new_game(9, 9, MockUi::new(vec![(4, 4)]))
+        .with_handicap(Handicap::Fixed(2))
+        .unwrap()
+        .with_player(Stone::White, engine(Stone::White).unwrap());
+    game.start_game().unwrap();
+    let state = game.board().get_state();
+    assert_eq!(state[6][2], Stone::Black);
+    assert_eq!(state[2][6], Stone::Black);
+    assert_eq!(state[4][4], Stone::Black);
+    // White moved first, then answered Black's move
+    assert_eq!(game.moves().len(), 3);
+
+    // anywhere else, it is a free handicap
+    let record = "(;GM[1]SZ[9]HA[2]AB[aa][bb]PL[W])";
+    let mut game = Game::from_sgf(record, MockUi::new(vec![(4, 4)]))
+        .unwrap()
+        .with_player(Stone::White, engine(Stone::White).unwrap());
+    game.start_game().unwrap();
+    let state = game.board().get_state();
+    assert_eq!(state[0][0], Stone::Black);
+    assert_eq!(state[1][1], Stone::Black);
+    assert_eq!(game.moves().len(), 3);
+}
+
+#[test]
+fn free_handicap_stones_reach_a_gtp_engine() {
+    let engine = gtp::GtpPlayer::spawn(Stone::White, env!("CARGO_BIN_EXE_go"), &["--gtp"]).unwrap();
+
+    // Black places the stones after the game starts, one on the engine's
+    // favourite first move, which it must not try to play on
+    let mut game = Game::new_game(9, 9, MockUi::new(vec![(3, 3), (5, 5), (4, 4)]))
+        .with_handicap(Handicap::Free(2))
+        .unwrap()
+        .with_player(Stone::White, engine);
+    game.start_game().unwrap();
+    assert_eq!(game.handicap_stones(), &[(3, 3), (5, 5)]);
+    let state = game.board().get_state();
+    assert_eq!(state[4][4], Stone::Black);
+    assert_eq!(game.moves().len(), 3);
+}
+
+/// Counts what it is shown, and would quit the game if it were ever asked
+/// for a move.
+#[derive(Default)]
+struct Spectator {
+    views: Rc<Cell<usize>>,
+    scored: Rc<Cell<bool>>,
+}
+
+impl UserInterface for Spectator {
+    fn view(&mut self, _game: &GameView) -> anyhow::Result<()> {
+        self.views.set(self.views.get() + 1);
+        Ok(())
+    }
+
+    fn input(&mut self, _board: &Board) -> anyhow::Result<UserAction> {
+        Ok(UserAction::Quit)
+    }
+
+    fn show_score(&mut self, _score: &Score) -> anyhow::Result<()> {
+        self.scored.set(true);
+        Ok(())
+    }
+}
+
+#[test]
+fn engines_play_each_other_to_the_end() {
+    let engine = |stone| gtp::GtpPlayer::spawn(stone, env!("CARGO_BIN_EXE_go"), &["--gtp"]);
+    let spectator = Spectator::default();
+    let (views, scored) = (spectator.views.clone(), spectator.scored.clone());
+
+    let mut game = Game::new_game(5, 5, Spectator::default())
+        .with_komi(0.5)
+        .with_player(Stone::Black, engine(Stone::Black).unwrap())
+        .with_player(Stone::White, engine(Stone::White).unwrap())
+        .with_display(spectator);
+    game.start_game().unwrap();
+
+    // nobody quit, so both engines passed and accepted the count
+    assert!(game.result().is_some());
+    assert!(scored.get());
+    assert!(views.get() > game.moves().len());
+}